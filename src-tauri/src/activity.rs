@@ -0,0 +1,186 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{is_human_prompt, timestamp_millis, unique_responses};
+use crate::storage::Storage;
+use crate::types::{ConversationMessage, Session};
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Granularity {
+    Hour,
+    #[default]
+    Day,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ActivityQuery {
+    pub granularity: Granularity,
+    /// Project path or project id.
+    pub project: Option<String>,
+    /// Inclusive bounds, in milliseconds since the epoch.
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// Offset of the viewer's timezone from UTC, so buckets follow local
+    /// hours and days.
+    pub utc_offset_minutes: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityBucket {
+    /// Start of the bucket, in milliseconds since the epoch.
+    pub start: i64,
+    pub project_id: String,
+    pub project: String,
+    pub messages: usize,
+    pub prompts: usize,
+    pub tokens: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayActivity {
+    /// Local calendar date, `YYYY-MM-DD`.
+    pub date: String,
+    pub messages: usize,
+    pub prompts: usize,
+    pub tokens: u64,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityReport {
+    pub granularity: Granularity,
+    pub buckets: Vec<ActivityBucket>,
+    pub days: Vec<DayActivity>,
+}
+
+struct ActivityAccumulator<'q> {
+    query: &'q ActivityQuery,
+    buckets: BTreeMap<(i64, String), ActivityBucket>,
+    days: BTreeMap<String, DayActivity>,
+}
+
+impl<'q> ActivityAccumulator<'q> {
+    fn new(query: &'q ActivityQuery) -> Self {
+        ActivityAccumulator {
+            query,
+            buckets: BTreeMap::new(),
+            days: BTreeMap::new(),
+        }
+    }
+
+    fn offset_ms(&self) -> i64 {
+        self.query.utc_offset_minutes as i64 * 60 * 1000
+    }
+
+    fn bucket_start(&self, ms: i64) -> i64 {
+        let size = match self.query.granularity {
+            Granularity::Hour => HOUR_MS,
+            Granularity::Day => DAY_MS,
+        };
+        let local = ms + self.offset_ms();
+        local.div_euclid(size) * size - self.offset_ms()
+    }
+
+    fn local_date(&self, ms: i64) -> String {
+        DateTime::from_timestamp_millis(ms + self.offset_ms())
+            .map(|t| t.date_naive().format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+
+    /// When a user or assistant message was written, if within the query's
+    /// bounds.
+    fn message_time(&self, msg: &ConversationMessage) -> Option<i64> {
+        if msg.msg_type != "user" && msg.msg_type != "assistant" {
+            return None;
+        }
+        let ms = msg.timestamp.as_deref().and_then(timestamp_millis)?;
+        let in_range = self.query.from.is_none_or(|from| ms >= from)
+            && self.query.to.is_none_or(|to| ms <= to);
+        in_range.then_some(ms)
+    }
+
+    fn entries(&mut self, ms: i64, session: &Session) -> (&mut ActivityBucket, &mut DayActivity) {
+        let start = self.bucket_start(ms);
+        let date = self.local_date(ms);
+        let bucket = self
+            .buckets
+            .entry((start, session.project_id.clone()))
+            .or_insert_with(|| ActivityBucket {
+                start,
+                project_id: session.project_id.clone(),
+                project: session.project.clone(),
+                ..Default::default()
+            });
+        let day = self
+            .days
+            .entry(date.clone())
+            .or_insert_with(|| DayActivity {
+                date,
+                ..Default::default()
+            });
+        (bucket, day)
+    }
+
+    fn add_session(&mut self, session: &Session, records: &[ConversationMessage]) {
+        let mut session_days = HashSet::new();
+        for msg in records {
+            let ms = match self.message_time(msg) {
+                Some(ms) => ms,
+                None => continue,
+            };
+            let is_prompt = is_human_prompt(msg);
+            let (bucket, day) = self.entries(ms, session);
+            bucket.messages += 1;
+            bucket.prompts += is_prompt as usize;
+            day.messages += 1;
+            day.prompts += is_prompt as usize;
+            if session_days.insert(day.date.clone()) {
+                day.sessions += 1;
+            }
+        }
+
+        // Usage repeats on every line of a multi-block response
+        for msg in unique_responses(records) {
+            let usage = match msg.message.as_ref().and_then(|m| m.usage.as_ref()) {
+                Some(usage) => usage,
+                None => continue,
+            };
+            if let Some(ms) = self.message_time(msg) {
+                let (bucket, day) = self.entries(ms, session);
+                bucket.tokens += usage.total();
+                day.tokens += usage.total();
+            }
+        }
+    }
+
+    fn finish(self) -> ActivityReport {
+        ActivityReport {
+            granularity: self.query.granularity,
+            buckets: self.buckets.into_values().collect(),
+            days: self.days.into_values().collect(),
+        }
+    }
+}
+
+/// Buckets every message timestamp across all sessions by time and project.
+pub async fn collect_activity(storage: &Storage, query: &ActivityQuery) -> Result<ActivityReport> {
+    let mut activity = ActivityAccumulator::new(query);
+    storage
+        .for_each_session(query.project.as_deref(), |session, records| {
+            activity.add_session(session, records)
+        })
+        .await?;
+
+    Ok(activity.finish())
+}
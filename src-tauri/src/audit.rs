@@ -0,0 +1,599 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{tool_calls, ToolCall};
+use crate::storage::Storage;
+use crate::tools::ToolInput;
+use crate::types::ConversationMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// What a rule looks for.
+///
+/// Shell patterns are whitespace-separated words that must appear in order
+/// within one command of a Bash call (commands are split at `&&`, `||` and
+/// `;`). A word ending in `*` matches by prefix, and a short flag such as
+/// `-rf` matches any flag cluster containing those letters (`-fr`, `-rfv`).
+/// Flags are case-sensitive, so `rm -R` needs patterns of its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RuleKind {
+    ShellCommand {
+        patterns: Vec<String>,
+    },
+    /// File edits outside the session's project directory.
+    #[serde(rename_all = "camelCase")]
+    WriteOutsideProject {
+        #[serde(default)]
+        allowed_paths: Vec<String>,
+    },
+    /// Reads of matching paths, by the Read tool or in Bash commands. A
+    /// pattern matches whole path components, so `.env` matches
+    /// `app/.env.local` but not `environment.ts`.
+    SensitivePath {
+        patterns: Vec<String>,
+    },
+    /// WebFetch calls and URLs in Bash commands whose host is not listed.
+    /// Subdomains of an allowed host are allowed too.
+    #[serde(rename_all = "camelCase")]
+    NetworkFetch {
+        allowed_hosts: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRule {
+    pub id: String,
+    pub description: String,
+    pub severity: Severity,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub kind: RuleKind,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditConfig {
+    pub rules: Vec<AuditRule>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        let rule = |id: &str, description: &str, severity, kind| AuditRule {
+            id: id.to_string(),
+            description: description.to_string(),
+            severity,
+            enabled: true,
+            kind,
+        };
+
+        AuditConfig {
+            rules: vec![
+                rule(
+                    "destructive-shell",
+                    "Destructive shell command",
+                    Severity::High,
+                    RuleKind::ShellCommand {
+                        patterns: strings(&[
+                            "rm -rf",
+                            "rm -Rf",
+                            "rm -r -f",
+                            "rm -f -r",
+                            "rm -R -f",
+                            "rm -f -R",
+                            "rm --recursive --force",
+                            "git reset --hard",
+                            "git clean -f",
+                            "git checkout -- .",
+                            "mkfs*",
+                            "dd of=/dev/*",
+                            "chmod -R 777",
+                            "curl | sh",
+                            "curl | bash",
+                            "wget | sh",
+                            "wget | bash",
+                        ]),
+                    },
+                ),
+                rule(
+                    "force-push",
+                    "Force-push to a git remote",
+                    Severity::High,
+                    RuleKind::ShellCommand {
+                        patterns: strings(&[
+                            "git push --force",
+                            "git push -f",
+                            "git push --force-with-lease",
+                        ]),
+                    },
+                ),
+                rule(
+                    "write-outside-project",
+                    "File written outside the project directory",
+                    Severity::Medium,
+                    RuleKind::WriteOutsideProject {
+                        allowed_paths: strings(&["/tmp"]),
+                    },
+                ),
+                rule(
+                    "credential-read",
+                    "Credential or secret file read",
+                    Severity::High,
+                    RuleKind::SensitivePath {
+                        patterns: strings(&[
+                            ".env",
+                            ".ssh",
+                            "id_rsa",
+                            "id_ed25519",
+                            ".aws/credentials",
+                            ".netrc",
+                            ".npmrc",
+                            ".pypirc",
+                            ".git-credentials",
+                            ".docker/config.json",
+                            ".kube/config",
+                        ]),
+                    },
+                ),
+                rule(
+                    "unknown-host",
+                    "Network fetch to a host that is not allowlisted",
+                    Severity::Low,
+                    RuleKind::NetworkFetch {
+                        allowed_hosts: strings(&[
+                            "github.com",
+                            "githubusercontent.com",
+                            "crates.io",
+                            "docs.rs",
+                            "rust-lang.org",
+                            "npmjs.com",
+                            "npmjs.org",
+                            "pypi.org",
+                            "python.org",
+                            "developer.mozilla.org",
+                            "stackoverflow.com",
+                            "anthropic.com",
+                            "localhost",
+                            "127.0.0.1",
+                        ]),
+                    },
+                ),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFinding {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    pub session_id: String,
+    pub project: Option<String>,
+    /// The assistant message that made the tool call.
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub tool: String,
+    pub tool_use_id: Option<String>,
+    /// The command, path or URL that matched.
+    pub evidence: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub sessions_scanned: usize,
+    pub findings: Vec<AuditFinding>,
+    pub by_severity: BTreeMap<Severity, usize>,
+    pub by_rule: BTreeMap<String, usize>,
+}
+
+impl AuditReport {
+    fn add(&mut self, findings: Vec<AuditFinding>) {
+        self.sessions_scanned += 1;
+        for finding in &findings {
+            *self.by_severity.entry(finding.severity).or_insert(0) += 1;
+            *self.by_rule.entry(finding.rule_id.clone()).or_insert(0) += 1;
+        }
+        self.findings.extend(findings);
+    }
+}
+
+/// Splits a shell command line into commands, each a list of words. Pipes
+/// stay within a command as a `|` word.
+fn shell_commands(command_line: &str) -> Vec<Vec<String>> {
+    let spaced = command_line
+        .replace("&&", " ; ")
+        .replace("||", " ; ")
+        .replace('|', " | ")
+        .replace('\n', " ; ");
+    spaced
+        .split(';')
+        .map(|command| {
+            command
+                .split_whitespace()
+                .map(|w| w.trim_matches(|c| c == '"' || c == '\'').to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|words| !words.is_empty())
+        .collect()
+}
+
+fn word_matches(pattern: &str, word: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        return word.starts_with(prefix);
+    }
+    let is_short_flags = |w: &str| w.len() > 1 && w.starts_with('-') && !w.starts_with("--");
+    if is_short_flags(pattern) && is_short_flags(word) {
+        return pattern[1..].chars().all(|c| word[1..].contains(c));
+    }
+    pattern == word
+}
+
+fn command_matches(pattern: &str, words: &[String]) -> bool {
+    let mut words = words.iter();
+    pattern
+        .split_whitespace()
+        .all(|p| words.any(|w| word_matches(p, w)))
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_matches('/');
+    path.match_indices(pattern).any(|(start, _)| {
+        let before = path[..start].chars().next_back();
+        let after = path[start + pattern.len()..].chars().next();
+        before.is_none_or(|c| c == '/' || c == '\\' || c == '~')
+            && after.is_none_or(|c| c == '/' || c == '\\' || c == '.')
+    })
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn is_within(path: &str, base: &str, cwd: Option<&str>) -> bool {
+    let path = Path::new(path);
+    let resolved = match cwd {
+        Some(cwd) if path.is_relative() => Path::new(cwd).join(path),
+        _ => path.to_path_buf(),
+    };
+    normalize(&resolved).starts_with(normalize(Path::new(base)))
+}
+
+fn url_host(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// The host of `url` unless it, or a domain it belongs to, is allowed.
+fn unknown_host<'a>(url: &'a str, allowed: &[String]) -> Option<&'a str> {
+    let host = url_host(url)?;
+    let lower = host.to_lowercase();
+    let is_allowed = allowed.iter().any(|a| {
+        let a = a.to_lowercase();
+        lower == a || lower.ends_with(&format!(".{}", a))
+    });
+    (!is_allowed).then_some(host)
+}
+
+/// What a rule can be checked against in one tool call.
+struct CallSubject<'a> {
+    command: Option<&'a str>,
+    read_path: Option<&'a str>,
+    written_path: Option<&'a str>,
+    url: Option<&'a str>,
+}
+
+fn check_rule(
+    rule: &AuditRule,
+    subject: &CallSubject,
+    project: Option<&str>,
+    cwd: Option<&str>,
+) -> Option<(String, String)> {
+    match rule.kind {
+        RuleKind::ShellCommand { ref patterns } => {
+            let command = subject.command?;
+            let commands = shell_commands(command);
+            let pattern = patterns
+                .iter()
+                .find(|p| commands.iter().any(|words| command_matches(p, words)))?;
+            Some((format!("matches `{}`", pattern), command.to_string()))
+        }
+        RuleKind::WriteOutsideProject { ref allowed_paths } => {
+            let path = subject.written_path?;
+            let project = project?;
+            let inside = is_within(path, project, cwd)
+                || allowed_paths.iter().any(|a| is_within(path, a, cwd));
+            (!inside).then(|| (format!("outside {}", project), path.to_string()))
+        }
+        RuleKind::SensitivePath { ref patterns } => {
+            if let Some(path) = subject.read_path {
+                let pattern = patterns.iter().find(|p| path_matches(p, path))?;
+                return Some((format!("matches `{}`", pattern), path.to_string()));
+            }
+            let words: Vec<String> = shell_commands(subject.command?).concat();
+            patterns.iter().find_map(|p| {
+                let word = words.iter().find(|w| path_matches(p, w))?;
+                Some((format!("matches `{}`", p), word.clone()))
+            })
+        }
+        RuleKind::NetworkFetch { ref allowed_hosts } => {
+            if let Some(url) = subject.url {
+                let host = unknown_host(url, allowed_hosts)?;
+                return Some((format!("host {}", host), url.to_string()));
+            }
+            shell_commands(subject.command?)
+                .concat()
+                .into_iter()
+                .find_map(|url| {
+                    let host = unknown_host(&url, allowed_hosts)?.to_string();
+                    Some((format!("host {}", host), url))
+                })
+        }
+    }
+}
+
+fn audit_call(
+    config: &AuditConfig,
+    session_id: &str,
+    project: Option<&str>,
+    call: &ToolCall,
+) -> Vec<AuditFinding> {
+    let tool = call.tool();
+    let subject = match tool {
+        ToolInput::Bash(ref i) => CallSubject {
+            command: Some(&i.command),
+            read_path: None,
+            written_path: None,
+            url: None,
+        },
+        ToolInput::Read(ref i) => CallSubject {
+            command: None,
+            read_path: Some(&i.file_path),
+            written_path: None,
+            url: None,
+        },
+        ToolInput::WebFetch(ref i) => CallSubject {
+            command: None,
+            read_path: None,
+            written_path: None,
+            url: Some(&i.url),
+        },
+        _ => CallSubject {
+            command: None,
+            read_path: None,
+            written_path: tool.edited_file(),
+            url: None,
+        },
+    };
+    let cwd = call.message.cwd.as_deref();
+
+    config
+        .rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| {
+            let (detail, evidence) = check_rule(rule, &subject, project, cwd)?;
+            Some(AuditFinding {
+                rule_id: rule.id.clone(),
+                severity: rule.severity,
+                message: format!("{}: {}", rule.description, detail),
+                session_id: session_id.to_string(),
+                project: project.map(str::to_string),
+                uuid: call.message.uuid.clone(),
+                timestamp: call.message.timestamp.clone(),
+                tool: call.name.to_string(),
+                tool_use_id: call.id.map(str::to_string),
+                evidence,
+            })
+        })
+        .collect()
+}
+
+pub fn audit_records(
+    config: &AuditConfig,
+    session_id: &str,
+    project: Option<&str>,
+    records: &[ConversationMessage],
+) -> Vec<AuditFinding> {
+    tool_calls(records)
+        .iter()
+        .flat_map(|call| audit_call(config, session_id, project, call))
+        .collect()
+}
+
+pub async fn audit_session(
+    storage: &Storage,
+    config: &AuditConfig,
+    session_id: &str,
+) -> Result<AuditReport> {
+    let records = storage.read_own_session_records(session_id).await?;
+    let project = storage.get_session_project_path(session_id).await?;
+
+    let mut report = AuditReport::default();
+    report.add(audit_records(
+        config,
+        session_id,
+        project.as_deref(),
+        &records,
+    ));
+    Ok(report)
+}
+
+/// Scans every session file, including subagent and headless sessions,
+/// most severe findings first.
+pub async fn audit_all_sessions(storage: &Storage, config: &AuditConfig) -> Result<AuditReport> {
+    let mut report = AuditReport::default();
+    storage
+        .for_each_transcript(
+            |_| true,
+            |transcript, records| {
+                report.add(audit_records(
+                    config,
+                    &transcript.session_id,
+                    Some(&transcript.project),
+                    records,
+                ))
+            },
+        )
+        .await;
+
+    report.findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(b.timestamp.cmp(&a.timestamp))
+    });
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(items: &[&str]) -> Vec<String> {
+        strings(items)
+    }
+
+    fn destructive_patterns() -> Vec<String> {
+        let config = AuditConfig::default();
+        match config.rules[0].kind {
+            RuleKind::ShellCommand { ref patterns } => patterns.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn shell_commands_split_at_separators() {
+        assert_eq!(
+            shell_commands("cd app && rm -rf 'build' || echo done; ls\npwd"),
+            vec![
+                words(&["cd", "app"]),
+                words(&["rm", "-rf", "build"]),
+                words(&["echo", "done"]),
+                words(&["ls"]),
+                words(&["pwd"]),
+            ]
+        );
+        assert_eq!(
+            shell_commands("curl -s https://x.sh|sh\nls"),
+            vec![
+                words(&["curl", "-s", "https://x.sh", "|", "sh"]),
+                words(&["ls"])
+            ]
+        );
+        assert!(shell_commands(" ; && ").is_empty());
+    }
+
+    #[test]
+    fn word_matches_prefixes_and_flag_clusters() {
+        assert!(word_matches("mkfs*", "mkfs.ext4"));
+        assert!(!word_matches("mkfs*", "fsck"));
+        assert!(word_matches("-rf", "-fr"));
+        assert!(word_matches("-rf", "-rfv"));
+        assert!(!word_matches("-rf", "-r"));
+        assert!(!word_matches("-rf", "-Rf"));
+        assert!(!word_matches("-rf", "--rf"));
+        assert!(word_matches("--force", "--force"));
+        assert!(!word_matches("--force", "--force-with-lease"));
+    }
+
+    #[test]
+    fn default_rules_catch_recursive_force_removal() {
+        let patterns = destructive_patterns();
+        let matches = |line: &str| {
+            shell_commands(line)
+                .iter()
+                .any(|cmd| patterns.iter().any(|p| command_matches(p, cmd)))
+        };
+        for line in [
+            "rm -rf build",
+            "rm -fr build",
+            "rm -Rf build",
+            "rm -fR build",
+            "rm -R -f build",
+            "rm -f -R build",
+            "rm -r -f build",
+            "rm --recursive --force build",
+        ] {
+            assert!(matches(line), "{line}");
+        }
+        for line in ["rm -f build", "rm -r build", "echo rm; rm build"] {
+            assert!(!matches(line), "{line}");
+        }
+    }
+
+    #[test]
+    fn path_matches_whole_components() {
+        assert!(path_matches(".env", "app/.env"));
+        assert!(path_matches(".env", "app/.env.local"));
+        assert!(path_matches(".env", "~/.env"));
+        assert!(!path_matches(".env", "src/environment.ts"));
+        assert!(!path_matches(".env", "app/my.env"));
+        assert!(path_matches(".aws/credentials", "/home/a/.aws/credentials"));
+        assert!(path_matches("/.ssh/", "/home/a/.ssh/id_rsa"));
+        assert!(path_matches("id_rsa", "C:\\Users\\a\\id_rsa.pub"));
+    }
+
+    #[test]
+    fn url_host_strips_scheme_credentials_and_port() {
+        assert_eq!(url_host("https://docs.rs/serde"), Some("docs.rs"));
+        assert_eq!(
+            url_host("http://user:pw@example.com:8080/x"),
+            Some("example.com")
+        );
+        assert_eq!(url_host("https://example.com?q=1"), Some("example.com"));
+        assert_eq!(url_host("http://[::1]:3000/"), Some("::1"));
+        assert_eq!(url_host("ftp://example.com"), None);
+        assert_eq!(url_host("https:///path"), None);
+    }
+
+    #[test]
+    fn is_within_resolves_relative_and_parent_paths() {
+        assert!(is_within("/home/a/api/src/main.rs", "/home/a/api", None));
+        assert!(is_within("src/main.rs", "/home/a/api", Some("/home/a/api")));
+        assert!(!is_within("../other/x", "/home/a/api", Some("/home/a/api")));
+        assert!(!is_within("/home/a/api/../secret", "/home/a/api", None));
+        assert!(!is_within("/home/a/api-old/x", "/home/a/api", None));
+        assert!(is_within("/tmp/./x", "/tmp", None));
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::line_index::tool_result_text;
+use crate::stats::{timestamp_millis, tool_calls, ToolCall};
+use crate::storage::{Storage, Transcript};
+use crate::tools::{ToolInput, ToolOutput};
+use crate::types::ConversationMessage;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BashHistoryQuery {
+    /// Project path or project id.
+    pub project: Option<String>,
+    pub session_id: Option<String>,
+    /// Case-insensitive substring of the command or its description.
+    pub text: Option<String>,
+    pub errors_only: bool,
+    /// Inclusive bounds, in milliseconds since the epoch.
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BashCommand {
+    pub session_id: String,
+    pub project_id: String,
+    pub project: String,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub command: String,
+    pub description: Option<String>,
+    pub cwd: Option<String>,
+    pub run_in_background: bool,
+    pub is_error: bool,
+    /// Parsed from the result of failed commands, which Claude Code reports
+    /// as `Exit code N`.
+    pub exit_code: Option<i32>,
+    pub interrupted: bool,
+}
+
+fn exit_code(result_text: &str) -> Option<i32> {
+    let first_line = result_text.lines().next()?;
+    let rest = first_line.trim_start_matches("Error: ");
+    rest.strip_prefix("Exit code ")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn bash_command(session: &Transcript, call: &ToolCall) -> Option<BashCommand> {
+    let input = match call.tool() {
+        ToolInput::Bash(input) => input,
+        _ => return None,
+    };
+    let interrupted = matches!(call.output(), Some(ToolOutput::Bash(ref o)) if o.interrupted);
+    let exit_code = call
+        .result
+        .as_ref()
+        .filter(|r| r.is_error)
+        .and_then(|r| r.content)
+        .and_then(|content| exit_code(&tool_result_text(content)));
+
+    Some(BashCommand {
+        session_id: session.session_id.clone(),
+        project_id: session.project_id.clone(),
+        project: session.project.clone(),
+        uuid: call.message.uuid.clone(),
+        timestamp: call.message.timestamp.clone(),
+        command: input.command,
+        description: input.description,
+        cwd: call.message.cwd.clone(),
+        run_in_background: input.run_in_background.unwrap_or(false),
+        is_error: call.is_error(),
+        exit_code,
+        interrupted,
+    })
+}
+
+fn matches_query(query: &BashHistoryQuery, command: &BashCommand) -> bool {
+    if query.errors_only && !command.is_error {
+        return false;
+    }
+    if let Some(ref text) = query.text {
+        let needle = text.to_lowercase();
+        let in_description = command
+            .description
+            .as_deref()
+            .is_some_and(|d| d.to_lowercase().contains(&needle));
+        if !command.command.to_lowercase().contains(&needle) && !in_description {
+            return false;
+        }
+    }
+    if query.from.is_some() || query.to.is_some() {
+        let ms = match command.timestamp.as_deref().and_then(timestamp_millis) {
+            Some(ms) => ms,
+            None => return false,
+        };
+        if query.from.is_some_and(|from| ms < from) || query.to.is_some_and(|to| ms > to) {
+            return false;
+        }
+    }
+    true
+}
+
+fn session_bash_commands(
+    session: &Transcript,
+    records: &[ConversationMessage],
+) -> Vec<BashCommand> {
+    tool_calls(records)
+        .iter()
+        .filter_map(|call| bash_command(session, call))
+        .collect()
+}
+
+/// Every Bash command Claude ran in any session file, including subagent
+/// and headless sessions, newest first.
+pub async fn collect_bash_history(
+    storage: &Storage,
+    query: &BashHistoryQuery,
+) -> Result<Vec<BashCommand>> {
+    let mut commands = Vec::new();
+    let include = |transcript: &Transcript| {
+        query
+            .project
+            .as_deref()
+            .is_none_or(|project| transcript.in_project(project))
+            && query
+                .session_id
+                .as_ref()
+                .is_none_or(|id| id == &transcript.session_id)
+    };
+    storage
+        .for_each_transcript(include, |transcript, records| {
+            commands.extend(
+                session_bash_commands(transcript, records)
+                    .into_iter()
+                    .filter(|c| matches_query(query, c)),
+            )
+        })
+        .await;
+
+    commands.sort_by_cached_key(|c| {
+        std::cmp::Reverse(c.timestamp.as_deref().and_then(timestamp_millis))
+    });
+    if let Some(limit) = query.limit {
+        commands.truncate(limit);
+    }
+    Ok(commands)
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::compaction::is_compact_summary;
+use crate::stats::{timestamp_millis, unique_responses, SYNTHETIC_MODEL};
+use crate::storage::Storage;
+use crate::types::{ConversationMessage, TokenUsage};
+
+/// Prompt cache entries expire after five minutes without a hit.
+const CACHE_TTL_MS: i64 = 5 * 60 * 1000;
+
+/// Prompts shorter than this are never cached.
+const MIN_CACHEABLE_TOKENS: u64 = 1024;
+
+/// Cache reads are billed at a tenth of the input price and cache writes
+/// (five minute TTL) at a quarter more.
+const CACHE_READ_MULTIPLIER: f64 = 0.1;
+const CACHE_WRITE_MULTIPLIER: f64 = 1.25;
+
+/// Base input price in USD per million tokens, by model id fragment. The
+/// first match wins, so more specific fragments come first.
+///
+/// Source: https://docs.anthropic.com/en/docs/about-claude/pricing as of
+/// November 2025. Update this table when prices change.
+const INPUT_PRICES: &[(&str, f64)] = &[
+    ("opus-4-1", 15.0),
+    ("opus-4-2025", 15.0),
+    ("3-opus", 15.0),
+    ("opus", 5.0),
+    ("sonnet", 3.0),
+    ("3-5-haiku", 0.8),
+    ("3-haiku", 0.25),
+    ("haiku", 1.0),
+];
+
+fn input_price(model: &str) -> Option<f64> {
+    INPUT_PRICES
+        .iter()
+        .find(|(fragment, _)| model.contains(fragment))
+        .map(|&(_, price)| price)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEfficiency {
+    pub responses: usize,
+    /// Input tokens sent without caching.
+    pub input_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Share of all input tokens served from the cache.
+    pub hit_ratio: f64,
+    /// Cache reads per token written to the cache; `None` if nothing was
+    /// written.
+    pub read_to_created_ratio: Option<f64>,
+    /// Input tokens' worth of billing avoided, after paying for cache writes.
+    pub tokens_saved: i64,
+    /// Savings in USD for models with a known price.
+    pub estimated_savings_usd: Option<f64>,
+    pub unpriced_models: Vec<String>,
+}
+
+impl CacheEfficiency {
+    fn add(&mut self, model: &str, usage: &TokenUsage) {
+        let read = usage.cache_read_input_tokens.unwrap_or(0);
+        let created = usage.cache_creation_input_tokens.unwrap_or(0);
+        self.responses += 1;
+        self.input_tokens += usage.input_tokens.unwrap_or(0);
+        self.cache_creation_tokens += created;
+        self.cache_read_tokens += read;
+
+        let saved = read as f64 * (1.0 - CACHE_READ_MULTIPLIER)
+            - created as f64 * (CACHE_WRITE_MULTIPLIER - 1.0);
+        match input_price(model) {
+            Some(price) => {
+                *self.estimated_savings_usd.get_or_insert(0.0) += saved * price / 1_000_000.0
+            }
+            None if read + created > 0 && !self.unpriced_models.iter().any(|m| m == model) => {
+                self.unpriced_models.push(model.to_string())
+            }
+            None => {}
+        }
+    }
+
+    fn merge(&mut self, other: &CacheEfficiency) {
+        self.responses += other.responses;
+        self.input_tokens += other.input_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        if let Some(savings) = other.estimated_savings_usd {
+            *self.estimated_savings_usd.get_or_insert(0.0) += savings;
+        }
+        for model in &other.unpriced_models {
+            if !self.unpriced_models.contains(model) {
+                self.unpriced_models.push(model.clone());
+            }
+        }
+    }
+
+    /// Fills in the ratios from the totals.
+    fn finish(&mut self) {
+        let read = self.cache_read_tokens as f64;
+        let created = self.cache_creation_tokens as f64;
+        let total = self.input_tokens as f64 + created + read;
+        self.hit_ratio = if total > 0.0 { read / total } else { 0.0 };
+        self.read_to_created_ratio = (created > 0.0).then(|| read / created);
+        self.tokens_saved = (read * (1.0 - CACHE_READ_MULTIPLIER)
+            - created * (CACHE_WRITE_MULTIPLIER - 1.0))
+            .round() as i64;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheBustCause {
+    /// The previous request was longer ago than the cache TTL.
+    IdleGap,
+    /// Caches are per model.
+    ModelSwitch,
+    /// The cached prefix changed, e.g. through edited tools or system prompt.
+    PrefixChanged,
+}
+
+/// A response that had to write most of its context to the cache again.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheBust {
+    pub cause: CacheBustCause,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    /// Time since the previous response, in milliseconds.
+    pub idle_ms: Option<i64>,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Extra cost of writing the context again instead of reading it.
+    pub estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCacheEfficiency {
+    #[serde(flatten)]
+    pub efficiency: CacheEfficiency,
+    pub busts: Vec<CacheBust>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCacheSummary {
+    pub session_id: String,
+    pub project: String,
+    #[serde(flatten)]
+    pub efficiency: CacheEfficiency,
+    pub busts: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCacheSummary {
+    pub project: String,
+    pub project_id: String,
+    #[serde(flatten)]
+    pub efficiency: CacheEfficiency,
+    pub busts: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheReport {
+    pub total: CacheEfficiency,
+    pub busts: usize,
+    /// Most cache traffic first.
+    pub projects: Vec<ProjectCacheSummary>,
+    pub sessions: Vec<SessionCacheSummary>,
+}
+
+struct PreviousResponse<'a> {
+    model: &'a str,
+    at: Option<i64>,
+    context_tokens: u64,
+}
+
+fn cache_bust(
+    msg: &ConversationMessage,
+    model: &str,
+    usage: &TokenUsage,
+    previous: &PreviousResponse,
+    at: Option<i64>,
+) -> Option<CacheBust> {
+    let read = usage.cache_read_input_tokens.unwrap_or(0);
+    let created = usage.cache_creation_input_tokens.unwrap_or(0);
+    // Most of what the previous request had cached was written again
+    if previous.context_tokens < MIN_CACHEABLE_TOKENS
+        || created <= read
+        || read * 2 >= previous.context_tokens
+    {
+        return None;
+    }
+
+    let idle_ms = at.zip(previous.at).map(|(at, prev)| at - prev);
+    let cause = if idle_ms.is_some_and(|idle| idle > CACHE_TTL_MS) {
+        CacheBustCause::IdleGap
+    } else if model != previous.model {
+        CacheBustCause::ModelSwitch
+    } else {
+        CacheBustCause::PrefixChanged
+    };
+    let rewritten = created.min(previous.context_tokens);
+
+    Some(CacheBust {
+        cause,
+        uuid: msg.uuid.clone(),
+        timestamp: msg.timestamp.clone(),
+        idle_ms,
+        cache_creation_tokens: created,
+        cache_read_tokens: read,
+        estimated_cost_usd: input_price(model).map(|price| {
+            rewritten as f64 * (CACHE_WRITE_MULTIPLIER - CACHE_READ_MULTIPLIER) * price
+                / 1_000_000.0
+        }),
+    })
+}
+
+/// Prompt cache usage of a session and the responses that lost the cache.
+pub fn session_cache_efficiency(records: &[ConversationMessage]) -> SessionCacheEfficiency {
+    let mut result = SessionCacheEfficiency::default();
+    let mut previous: Option<PreviousResponse> = None;
+
+    for msg in unique_responses(records) {
+        // Compaction starts a new prefix, so the next write is expected
+        if is_compact_summary(msg) {
+            previous = None;
+            continue;
+        }
+        if msg.msg_type != "assistant" {
+            continue;
+        }
+        let content = match msg.message.as_ref() {
+            Some(content) => content,
+            None => continue,
+        };
+        let (model, usage) = match (content.model.as_deref(), content.usage.as_ref()) {
+            (Some(model), Some(usage)) if model != SYNTHETIC_MODEL => (model, usage),
+            _ => continue,
+        };
+        result.efficiency.add(model, usage);
+
+        // Subagents keep caches of their own
+        if msg.is_sidechain == Some(true) {
+            continue;
+        }
+        let at = msg.timestamp.as_deref().and_then(timestamp_millis);
+        if let Some(ref prev) = previous {
+            result.busts.extend(cache_bust(msg, model, usage, prev, at));
+        }
+        previous = Some(PreviousResponse {
+            model,
+            at,
+            context_tokens: usage.context_size(),
+        });
+    }
+
+    result.efficiency.finish();
+    result
+}
+
+/// Cache efficiency per session and project, optionally limited to a
+/// project (path or id).
+pub async fn collect_cache_report(storage: &Storage, project: Option<&str>) -> Result<CacheReport> {
+    let mut report = CacheReport::default();
+    let mut projects: BTreeMap<String, ProjectCacheSummary> = BTreeMap::new();
+
+    storage
+        .for_each_session(project, |session, records| {
+            let result = session_cache_efficiency(records);
+            if result.efficiency.responses == 0 {
+                return;
+            }
+
+            let summary = projects
+                .entry(session.project_id.clone())
+                .or_insert_with(|| ProjectCacheSummary {
+                    project: session.project.clone(),
+                    project_id: session.project_id.clone(),
+                    efficiency: CacheEfficiency::default(),
+                    busts: 0,
+                });
+            summary.efficiency.merge(&result.efficiency);
+            summary.busts += result.busts.len();
+            report.total.merge(&result.efficiency);
+            report.busts += result.busts.len();
+            report.sessions.push(SessionCacheSummary {
+                session_id: session.id.clone(),
+                project: session.project.clone(),
+                efficiency: result.efficiency,
+                busts: result.busts.len(),
+            });
+        })
+        .await?;
+
+    report.total.finish();
+    report.projects = projects.into_values().collect();
+    for summary in &mut report.projects {
+        summary.efficiency.finish();
+    }
+    let traffic =
+        |e: &CacheEfficiency| std::cmp::Reverse(e.cache_read_tokens + e.cache_creation_tokens);
+    report.projects.sort_by_key(|p| traffic(&p.efficiency));
+    report.sessions.sort_by_key(|s| traffic(&s.efficiency));
+    Ok(report)
+}
@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::stats::timestamp_millis;
+use crate::types::{ConversationMessage, SessionChain};
+
+/// What a session file records about the session it continues.
+#[derive(Debug, Clone, Default)]
+pub struct SessionLinks {
+    /// The session this one continues: the earlier conversation Claude Code
+    /// copied in when the session was resumed, or else the session that
+    /// wrote a message one of `external_refs` points to.
+    pub previous: Option<String>,
+    /// Parent and summary leaf uuids that point outside this file.
+    pub external_refs: Vec<String>,
+    pub started_at: Option<i64>,
+}
+
+/// Reads the links of a session file. `previous` is only set when the file
+/// starts with a copied-in conversation; external refs are left for the
+/// caller to resolve, since that means looking at other files.
+pub fn session_links(session_id: &str, records: &[ConversationMessage]) -> SessionLinks {
+    let all_uuids: HashSet<&str> = records.iter().filter_map(|r| r.uuid.as_deref()).collect();
+    let mut links = SessionLinks::default();
+    let mut in_copied_prefix = true;
+    let mut first_message = true;
+
+    for record in records {
+        match record.session_id.as_deref() {
+            Some(id) if id != session_id => {
+                if in_copied_prefix {
+                    links.previous = Some(id.to_string());
+                }
+                continue;
+            }
+            Some(_) => in_copied_prefix = false,
+            None => {}
+        }
+
+        if links.started_at.is_none() {
+            links.started_at = record.timestamp.as_deref().and_then(timestamp_millis);
+        }
+
+        let external = |uuid: &Option<String>| {
+            uuid.as_deref()
+                .filter(|u| !all_uuids.contains(u))
+                .map(str::to_string)
+        };
+        match record.msg_type.as_str() {
+            "summary" => links.external_refs.extend(external(&record.leaf_uuid)),
+            "user" | "assistant" if first_message => {
+                first_message = false;
+                links.external_refs.extend(external(&record.parent_uuid));
+            }
+            _ => {}
+        }
+    }
+
+    links
+}
+
+/// Drops the records a resumed session copied in from the conversation it
+/// continues, so they are not counted twice. Records without a session id,
+/// such as summaries, are kept, as is every record of a file that has none
+/// of its own (e.g. subagent transcripts, which carry the parent's id).
+pub fn own_records(
+    session_id: &str,
+    records: Vec<ConversationMessage>,
+) -> Vec<ConversationMessage> {
+    if !records
+        .iter()
+        .any(|r| r.session_id.as_deref() == Some(session_id))
+    {
+        return records;
+    }
+    records
+        .into_iter()
+        .filter(|r| r.session_id.as_deref().is_none_or(|id| id == session_id))
+        .collect()
+}
+
+/// Links sessions into chains and returns the chain of every session that
+/// has a predecessor or a continuation.
+pub fn build_chains(links: &HashMap<String, SessionLinks>) -> HashMap<String, SessionChain> {
+    let previous: HashMap<&str, &str> = links
+        .iter()
+        .filter_map(|(id, l)| {
+            l.previous
+                .as_deref()
+                .filter(|p| p != id)
+                .map(|p| (id.as_str(), p))
+        })
+        .collect();
+
+    let mut next: HashMap<&str, &str> = HashMap::new();
+    for (&id, &prev) in &previous {
+        let started = |id: &str| links.get(id).and_then(|l| l.started_at);
+        let earlier = match next.get(prev) {
+            Some(&current) => (started(id), id) < (started(current), current),
+            None => true,
+        };
+        if earlier {
+            next.insert(prev, id);
+        }
+    }
+
+    let mut chains = HashMap::new();
+    for id in previous.keys().chain(next.keys()) {
+        if chains.contains_key(*id) {
+            continue;
+        }
+
+        let mut seen = HashSet::from([id.to_string()]);
+        let mut lineage = vec![id.to_string()];
+        let mut current = *id;
+        while let Some(&prev) = previous.get(current) {
+            if !seen.insert(prev.to_string()) {
+                break;
+            }
+            lineage.push(prev.to_string());
+            current = prev;
+        }
+        lineage.reverse();
+
+        current = id;
+        while let Some(&following) = next.get(current) {
+            if !seen.insert(following.to_string()) {
+                break;
+            }
+            lineage.push(following.to_string());
+            current = following;
+        }
+
+        chains.insert(
+            id.to_string(),
+            SessionChain {
+                previous: previous.get(id).map(|p| p.to_string()),
+                next: next.get(id).map(|n| n.to_string()),
+                lineage,
+            },
+        );
+    }
+    chains
+}
+
+/// Concatenates the conversations of a chain, oldest first, dropping the
+/// messages each resumed session copied from the one before it.
+pub fn merge_conversations(
+    conversations: Vec<Vec<ConversationMessage>>,
+) -> Vec<ConversationMessage> {
+    let mut seen_uuids = HashSet::new();
+    let mut seen_summaries = HashSet::new();
+    let mut merged = Vec::new();
+
+    for msg in conversations.into_iter().flatten() {
+        let is_new = match (&msg.uuid, &msg.summary) {
+            (Some(uuid), _) => seen_uuids.insert(uuid.clone()),
+            (None, Some(summary)) => seen_summaries.insert(summary.clone()),
+            (None, None) => true,
+        };
+        if is_new {
+            merged.push(msg);
+        }
+    }
+    merged
+}
@@ -2,12 +2,58 @@ use std::sync::Arc;
 
 use tauri::State;
 
+use crate::activity::{self, ActivityQuery, ActivityReport};
+use crate::audit::{self, AuditConfig, AuditReport};
+use crate::bash_history::{self, BashCommand, BashHistoryQuery};
+use crate::cache::{self, CacheReport, SessionCacheEfficiency};
+use crate::compaction::{self, CompactionSegment};
+use crate::compare::{self, SessionComparison};
+use crate::context_growth::{self, ContextGrowth};
+use crate::failures::{self, FailureTimeline};
+use crate::git_context::{self, GitContext};
+use crate::invocations::{self, InvocationReport, SessionInvocations};
+use crate::mcp::{self, McpReport};
+use crate::models::{self, ModelBreakdown};
+use crate::projects::Project;
+use crate::prompt_library::{PromptLibrary, SavedPrompt};
+use crate::prompts::{self, PromptQuery, PromptUsage};
+use crate::query::{self, SessionPage, SessionQuery};
+use crate::resume::{self, Shell, TerminalSettings};
 use crate::storage::Storage;
-use crate::types::{ConversationMessage, Session, StreamResult};
+use crate::tags::TagStore;
+use crate::thinking::{self, SessionThinking, ThinkingReport};
+use crate::todos::{self, TodoProgress};
+use crate::tools::{self, ToolCallRecord};
+use crate::types::{
+    ConversationMessage, ConversationWindow, Session, SessionStats, SessionSummary, StreamResult,
+};
+use crate::watcher::{WatcherHandle, WatcherStatus};
 
 #[tauri::command]
-pub async fn get_sessions(storage: State<'_, Arc<Storage>>) -> Result<Vec<Session>, String> {
-    storage.get_sessions().await.map_err(|e| e.to_string())
+pub async fn get_sessions(
+    storage: State<'_, Arc<Storage>>,
+    tags: State<'_, Arc<TagStore>>,
+) -> Result<Vec<Session>, String> {
+    let mut sessions = storage.get_sessions().await.map_err(|e| e.to_string())?;
+    let all_tags = tags.snapshot().await;
+    for session in &mut sessions {
+        session.tags = all_tags.get(&session.id).cloned().unwrap_or_default();
+    }
+    Ok(sessions)
+}
+
+#[tauri::command]
+pub async fn query_sessions(
+    query: SessionQuery,
+    storage: State<'_, Arc<Storage>>,
+    tags: State<'_, Arc<TagStore>>,
+) -> Result<SessionPage, String> {
+    let mut sessions = storage.get_sessions().await.map_err(|e| e.to_string())?;
+    if query.needs_stats() {
+        storage.fill_session_stats(&mut sessions).await;
+    }
+    let all_tags = tags.snapshot().await;
+    Ok(query::query_sessions(sessions, &all_tags, &query))
 }
 
 #[tauri::command]
@@ -15,14 +61,35 @@ pub async fn get_projects(storage: State<'_, Arc<Storage>>) -> Result<Vec<String
     storage.get_projects().await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_project_list(storage: State<'_, Arc<Storage>>) -> Result<Vec<Project>, String> {
+    storage.get_project_list().await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_conversation(
     session_id: String,
+    strip_thinking: Option<bool>,
     storage: State<'_, Arc<Storage>>,
 ) -> Result<Vec<ConversationMessage>, String> {
-    storage
+    let mut messages = storage
         .get_conversation(&session_id)
         .await
+        .map_err(|e| e.to_string())?;
+    if strip_thinking.unwrap_or(false) {
+        thinking::strip_thinking(&mut messages);
+    }
+    Ok(messages)
+}
+
+#[tauri::command]
+pub async fn get_chain_conversation(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Vec<ConversationMessage>, String> {
+    storage
+        .get_chain_conversation(&session_id)
+        .await
         .map_err(|e| e.to_string())
 }
 
@@ -38,13 +105,447 @@ pub async fn get_conversation_stream(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_conversation_window(
+    session_id: String,
+    start: usize,
+    count: usize,
+    max_tool_result_chars: Option<usize>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<ConversationWindow, String> {
+    storage
+        .get_conversation_window(&session_id, start, count, max_tool_result_chars)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tool_result_content(
+    session_id: String,
+    message_index: usize,
+    tool_use_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Option<serde_json::Value>, String> {
+    storage
+        .get_tool_result_content(&session_id, message_index, &tool_use_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_session_meta(
     session_id: String,
     storage: State<'_, Arc<Storage>>,
+    tags: State<'_, Arc<TagStore>>,
 ) -> Result<Option<Session>, String> {
-    storage
+    let mut session = storage
         .get_session_meta(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(ref mut session) = session {
+        session.tags = tags.get(&session.id).await;
+    }
+    Ok(session)
+}
+
+#[tauri::command]
+pub async fn get_session_stats(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Option<SessionStats>, String> {
+    Ok(storage.get_session_stats(&session_id).await)
+}
+
+#[tauri::command]
+pub async fn get_session_summary(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Option<SessionSummary>, String> {
+    Ok(storage.get_session_summary(&session_id).await)
+}
+
+#[tauri::command]
+pub async fn get_compaction_segments(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Vec<CompactionSegment>, String> {
+    // A resumed session's copied prefix holds its earlier segments, so
+    // segments are cut from the full transcript rather than own records.
+    let records = storage
+        .read_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(compaction::compaction_segments(&records))
+}
+
+#[tauri::command]
+pub async fn get_compaction_segment(
+    session_id: String,
+    index: usize,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Vec<ConversationMessage>, String> {
+    let records = storage
+        .read_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    compaction::segment_messages(&records, index)
+        .ok_or_else(|| format!("Session {} has no segment {}", session_id, index))
+}
+
+#[tauri::command]
+pub async fn get_tool_calls(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Vec<ToolCallRecord>, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(tools::tool_call_records(&records))
+}
+
+#[tauri::command]
+pub async fn get_todo_progress(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<TodoProgress, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(todos::todo_progress(&records))
+}
+
+#[tauri::command]
+pub async fn get_failure_timeline(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<FailureTimeline, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(failures::failure_timeline(&records))
+}
+
+#[tauri::command]
+pub async fn get_thinking(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<SessionThinking, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(thinking::session_thinking(&records))
+}
+
+#[tauri::command]
+pub async fn get_thinking_report(
+    storage: State<'_, Arc<Storage>>,
+) -> Result<ThinkingReport, String> {
+    thinking::collect_thinking_report(&storage)
         .await
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn get_model_breakdown(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<ModelBreakdown, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(models::model_breakdown(&records))
+}
+
+#[tauri::command]
+pub async fn get_context_growth(
+    session_id: String,
+    jumps: Option<usize>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<ContextGrowth, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(context_growth::context_growth(&records, jumps))
+}
+
+#[tauri::command]
+pub async fn get_session_cache_efficiency(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<SessionCacheEfficiency, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(cache::session_cache_efficiency(&records))
+}
+
+#[tauri::command]
+pub async fn get_cache_report(
+    project: Option<String>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<CacheReport, String> {
+    cache::collect_cache_report(&storage, project.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_session_invocations(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<SessionInvocations, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(invocations::session_invocations(&records))
+}
+
+#[tauri::command]
+pub async fn get_invocation_report(
+    project: Option<String>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<InvocationReport, String> {
+    invocations::collect_invocation_report(&storage, project.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_session_mcp_usage(
+    session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<McpReport, String> {
+    let records = storage
+        .read_own_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(mcp::session_mcp_usage(&records))
+}
+
+#[tauri::command]
+pub async fn get_mcp_report(
+    project: Option<String>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<McpReport, String> {
+    mcp::collect_mcp_report(&storage, project.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_session_git_context(
+    session_id: String,
+    include_commits: Option<bool>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<GitContext, String> {
+    let records = storage
+        .read_session_records(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let include_commits = include_commits.unwrap_or(false);
+
+    // Walking the repository is blocking work
+    tauri::async_runtime::spawn_blocking(move || {
+        git_context::build_git_context(&records, include_commits)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_resume_command(
+    session_id: String,
+    shell: Option<Shell>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Option<String>, String> {
+    let project_path = storage
+        .get_session_project_path(&session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let shell = shell.unwrap_or_else(Shell::platform_default);
+    Ok(project_path.map(|path| resume::resume_command(shell, &path, &session_id)))
+}
+
+#[tauri::command]
+pub async fn open_in_terminal(
+    session_id: String,
+    shell: Option<Shell>,
+    storage: State<'_, Arc<Storage>>,
+    settings: State<'_, Arc<TerminalSettings>>,
+) -> Result<(), String> {
+    let project_path = storage
+        .get_session_project_path(&session_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Unknown session {}", session_id))?;
+    let shell = shell.unwrap_or_else(Shell::platform_default);
+    let terminal = settings.terminal_for(shell);
+    let command = resume::resume_command(shell, &project_path, &session_id);
+    resume::open_in_terminal(&terminal, &command).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn compare_sessions(
+    left_session_id: String,
+    right_session_id: String,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<SessionComparison, String> {
+    let (left, right) = tokio::try_join!(
+        storage.read_own_session_records(&left_session_id),
+        storage.read_own_session_records(&right_session_id),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(compare::compare_sessions(
+        &left_session_id,
+        &left,
+        &right_session_id,
+        &right,
+    ))
+}
+
+#[tauri::command]
+pub async fn get_activity(
+    query: Option<ActivityQuery>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<ActivityReport, String> {
+    activity::collect_activity(&storage, &query.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_bash_history(
+    query: Option<BashHistoryQuery>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<Vec<BashCommand>, String> {
+    bash_history::collect_bash_history(&storage, &query.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_default_audit_config() -> Result<AuditConfig, String> {
+    Ok(AuditConfig::default())
+}
+
+#[tauri::command]
+pub async fn audit_session(
+    session_id: String,
+    config: Option<AuditConfig>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<AuditReport, String> {
+    audit::audit_session(&storage, &config.unwrap_or_default(), &session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_audit_report(
+    config: Option<AuditConfig>,
+    storage: State<'_, Arc<Storage>>,
+) -> Result<AuditReport, String> {
+    audit::audit_all_sessions(&storage, &config.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_prompts(
+    query: Option<PromptQuery>,
+    storage: State<'_, Arc<Storage>>,
+    library: State<'_, Arc<PromptLibrary>>,
+) -> Result<Vec<PromptUsage>, String> {
+    prompts::collect_prompts(&storage, &library, &query.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_saved_prompts(
+    library: State<'_, Arc<PromptLibrary>>,
+) -> Result<Vec<SavedPrompt>, String> {
+    Ok(library.snapshot().await)
+}
+
+#[tauri::command]
+pub async fn get_prompt_tags(
+    library: State<'_, Arc<PromptLibrary>>,
+) -> Result<Vec<String>, String> {
+    Ok(library.all_tags().await)
+}
+
+#[tauri::command]
+pub async fn save_prompt(
+    text: String,
+    title: Option<String>,
+    tags: Vec<String>,
+    library: State<'_, Arc<PromptLibrary>>,
+) -> Result<SavedPrompt, String> {
+    library
+        .save(&text, title, tags)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_saved_prompt(
+    key: String,
+    library: State<'_, Arc<PromptLibrary>>,
+) -> Result<bool, String> {
+    library.remove(&key).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_all_tags(tags: State<'_, Arc<TagStore>>) -> Result<Vec<String>, String> {
+    Ok(tags.all_tags().await)
+}
+
+#[tauri::command]
+pub async fn set_session_tags(
+    session_id: String,
+    tags: Vec<String>,
+    store: State<'_, Arc<TagStore>>,
+) -> Result<Vec<String>, String> {
+    store
+        .set(&session_id, tags)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_watcher_status(
+    watcher: State<'_, Arc<WatcherHandle>>,
+) -> Result<WatcherStatus, String> {
+    Ok(watcher.status())
+}
+
+#[tauri::command]
+pub async fn start_watcher(
+    watcher: State<'_, Arc<WatcherHandle>>,
+) -> Result<WatcherStatus, String> {
+    watcher.start().map_err(|e| e.to_string())?;
+    Ok(watcher.status())
+}
+
+#[tauri::command]
+pub async fn stop_watcher(watcher: State<'_, Arc<WatcherHandle>>) -> Result<WatcherStatus, String> {
+    watcher.stop();
+    Ok(watcher.status())
+}
+
+#[tauri::command]
+pub async fn restart_watcher(
+    watcher: State<'_, Arc<WatcherHandle>>,
+) -> Result<WatcherStatus, String> {
+    watcher.restart().map_err(|e| e.to_string())?;
+    Ok(watcher.status())
+}
@@ -0,0 +1,180 @@
+use serde::Serialize;
+
+use crate::stats::{message_text, token_totals};
+use crate::types::{ConversationMessage, TokenTotals};
+
+/// Opening of the summary message older Claude Code versions wrote without
+/// an `isCompactSummary` flag.
+const CONTINUATION_PREFIX: &str = "This session is being continued from a previous conversation";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionBoundary {
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    /// `manual` for `/compact`, `auto` when the context window filled up.
+    pub trigger: Option<String>,
+    /// Last message of the conversation that was compacted away.
+    pub logical_parent_uuid: Option<String>,
+    /// The summary Claude continued from.
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionSegment {
+    pub index: usize,
+    /// How this segment started; `None` for the original conversation.
+    pub boundary: Option<CompactionBoundary>,
+    pub message_count: usize,
+    pub first_message_at: Option<String>,
+    pub last_message_at: Option<String>,
+    /// Context size sent with the first response of the segment.
+    pub context_tokens_start: Option<u64>,
+    /// Context size when the segment ended, as reported by the compaction
+    /// if available, otherwise from the last response.
+    pub context_tokens_end: Option<u64>,
+    pub tokens: TokenTotals,
+}
+
+/// Messages between two compactions, with the context size the later one
+/// reported.
+struct SegmentRange {
+    boundary: Option<CompactionBoundary>,
+    pre_tokens: Option<u64>,
+    messages: Vec<ConversationMessage>,
+}
+
+/// True for the summary a compacted conversation continues from.
+pub fn is_compact_summary(msg: &ConversationMessage) -> bool {
+    msg.msg_type == "user"
+        && (msg.is_compact_summary == Some(true)
+            || message_text(msg)
+                .trim_start()
+                .starts_with(CONTINUATION_PREFIX))
+}
+
+fn split_segments(records: &[ConversationMessage]) -> Vec<SegmentRange> {
+    let mut segments = vec![SegmentRange {
+        boundary: None,
+        pre_tokens: None,
+        messages: Vec::new(),
+    }];
+    // A `compact_boundary` record is followed by the summary message, which
+    // belongs to the segment the boundary opened rather than a new one
+    let mut awaiting_summary = false;
+
+    for msg in records {
+        if msg.msg_type == "system" && msg.subtype.as_deref() == Some("compact_boundary") {
+            let metadata = msg.compact_metadata.as_ref();
+            if let Some(current) = segments.last_mut() {
+                current.pre_tokens = metadata.and_then(|m| m.pre_tokens);
+            }
+            segments.push(SegmentRange {
+                boundary: Some(CompactionBoundary {
+                    uuid: msg.uuid.clone(),
+                    timestamp: msg.timestamp.clone(),
+                    trigger: metadata.and_then(|m| m.trigger.clone()),
+                    logical_parent_uuid: msg.logical_parent_uuid.clone(),
+                    summary: None,
+                }),
+                pre_tokens: None,
+                messages: Vec::new(),
+            });
+            awaiting_summary = true;
+            continue;
+        }
+        if msg.msg_type != "user" && msg.msg_type != "assistant" {
+            continue;
+        }
+
+        if is_compact_summary(msg) {
+            let summary = Some(message_text(msg));
+            let current = segments.last_mut().expect("segments start non-empty");
+            match current.boundary {
+                Some(ref mut boundary) if awaiting_summary => boundary.summary = summary,
+                _ if current.messages.is_empty() && current.boundary.is_none() => {
+                    // A session file that opens with a summary was continued
+                    // from another session
+                    current.boundary = Some(CompactionBoundary {
+                        uuid: msg.uuid.clone(),
+                        timestamp: msg.timestamp.clone(),
+                        trigger: None,
+                        logical_parent_uuid: msg.logical_parent_uuid.clone(),
+                        summary,
+                    });
+                }
+                _ => segments.push(SegmentRange {
+                    boundary: Some(CompactionBoundary {
+                        uuid: msg.uuid.clone(),
+                        timestamp: msg.timestamp.clone(),
+                        trigger: None,
+                        logical_parent_uuid: msg.logical_parent_uuid.clone(),
+                        summary,
+                    }),
+                    pre_tokens: None,
+                    messages: Vec::new(),
+                }),
+            }
+        }
+        awaiting_summary = false;
+        segments
+            .last_mut()
+            .expect("segments start non-empty")
+            .messages
+            .push(msg.clone());
+    }
+
+    segments
+}
+
+fn context_size(msg: &ConversationMessage) -> Option<u64> {
+    if msg.msg_type != "assistant" {
+        return None;
+    }
+    msg.message
+        .as_ref()?
+        .usage
+        .as_ref()
+        .map(|u| u.context_size())
+}
+
+fn describe(index: usize, range: &SegmentRange) -> CompactionSegment {
+    let messages = &range.messages;
+    let context_tokens_end = range
+        .pre_tokens
+        .or_else(|| messages.iter().rev().find_map(context_size));
+
+    CompactionSegment {
+        index,
+        boundary: range.boundary.clone(),
+        message_count: messages.len(),
+        first_message_at: messages.iter().find_map(|m| m.timestamp.clone()),
+        last_message_at: messages.iter().rev().find_map(|m| m.timestamp.clone()),
+        context_tokens_start: messages.iter().find_map(context_size),
+        context_tokens_end,
+        tokens: token_totals(messages),
+    }
+}
+
+/// Splits a session at each compaction into the stretches of conversation
+/// that shared one context window.
+pub fn compaction_segments(records: &[ConversationMessage]) -> Vec<CompactionSegment> {
+    split_segments(records)
+        .iter()
+        .enumerate()
+        .map(|(i, range)| describe(i, range))
+        .collect()
+}
+
+/// The user and assistant messages of one segment, starting with the
+/// summary that opened it.
+pub fn segment_messages(
+    records: &[ConversationMessage],
+    index: usize,
+) -> Option<Vec<ConversationMessage>> {
+    split_segments(records)
+        .into_iter()
+        .nth(index)
+        .map(|range| range.messages)
+}
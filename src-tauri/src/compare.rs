@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::stats::{
+    compute_session_stats, edited_files, is_human_prompt, message_text, token_totals, tool_calls,
+    ToolCall,
+};
+use crate::types::{ConversationMessage, TokenTotals};
+
+const OUTCOME_PREVIEW_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSide {
+    pub session_id: String,
+    pub turns: usize,
+    pub tokens: TokenTotals,
+    pub total_tokens: u64,
+    pub duration_ms: u64,
+    pub models: Vec<String>,
+    pub tool_calls: usize,
+    pub tool_errors: usize,
+    pub tool_counts: BTreeMap<String, usize>,
+    pub files_touched: Vec<String>,
+    /// The final assistant text, which usually states what was achieved.
+    pub outcome: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallSummary {
+    pub name: String,
+    pub target: Option<String>,
+    pub is_error: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnComparison {
+    pub index: usize,
+    pub left_prompt: Option<String>,
+    pub right_prompt: Option<String>,
+    pub left_tools: Vec<ToolCallSummary>,
+    pub right_tools: Vec<ToolCallSummary>,
+    pub left_tokens: u64,
+    pub right_tokens: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StepKind {
+    /// Same tool with the same target on both sides.
+    Same,
+    /// Same tool, different arguments.
+    Changed,
+    LeftOnly,
+    RightOnly,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStep {
+    pub kind: StepKind,
+    pub left: Option<ToolCallSummary>,
+    pub right: Option<ToolCallSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionComparison {
+    pub left: SessionSide,
+    pub right: SessionSide,
+    pub turns: Vec<TurnComparison>,
+    pub tool_steps: Vec<ToolStep>,
+    pub files_only_left: Vec<String>,
+    pub files_only_right: Vec<String>,
+    pub files_in_both: Vec<String>,
+}
+
+fn summarize_call(call: &ToolCall) -> ToolCallSummary {
+    ToolCallSummary {
+        name: call.name.to_string(),
+        target: call.tool().target().map(str::to_string),
+        is_error: call.is_error(),
+    }
+}
+
+fn build_side(session_id: &str, messages: &[ConversationMessage]) -> SessionSide {
+    let stats = compute_session_stats(messages);
+    let calls = tool_calls(messages);
+
+    let mut tool_counts = BTreeMap::new();
+    for call in &calls {
+        *tool_counts.entry(call.name.to_string()).or_insert(0) += 1;
+    }
+
+    let outcome = messages
+        .iter()
+        .rev()
+        .filter(|m| m.msg_type == "assistant")
+        .map(message_text)
+        .find(|text| !text.trim().is_empty())
+        .map(|text| text.chars().take(OUTCOME_PREVIEW_CHARS).collect());
+
+    SessionSide {
+        session_id: session_id.to_string(),
+        turns: stats.user_turns,
+        tokens: stats.tokens,
+        total_tokens: stats.total_tokens,
+        duration_ms: stats.duration_ms,
+        models: stats.models,
+        tool_calls: calls.len(),
+        tool_errors: calls.iter().filter(|c| c.is_error()).count(),
+        tool_counts,
+        files_touched: edited_files(&calls).into_iter().collect(),
+        outcome,
+    }
+}
+
+/// Splits a conversation into turns, each starting at a user prompt and
+/// running until the next one.
+fn split_turns(messages: &[ConversationMessage]) -> Vec<&[ConversationMessage]> {
+    let starts: Vec<usize> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| is_human_prompt(m))
+        .map(|(i, _)| i)
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let end = starts.get(n + 1).copied().unwrap_or(messages.len());
+            &messages[start..end]
+        })
+        .collect()
+}
+
+fn compare_turns(
+    left: &[ConversationMessage],
+    right: &[ConversationMessage],
+) -> Vec<TurnComparison> {
+    let left_turns = split_turns(left);
+    let right_turns = split_turns(right);
+    let count = left_turns.len().max(right_turns.len());
+
+    let prompt = |turn: Option<&&[ConversationMessage]>| turn.map(|t| message_text(&t[0]));
+    let tools = |turn: Option<&&[ConversationMessage]>| {
+        turn.map(|t| tool_calls(t).iter().map(summarize_call).collect())
+            .unwrap_or_default()
+    };
+    let tokens = |turn: Option<&&[ConversationMessage]>| {
+        turn.map(|t| token_totals(t.iter()).total()).unwrap_or(0)
+    };
+
+    (0..count)
+        .map(|i| {
+            let (l, r) = (left_turns.get(i), right_turns.get(i));
+            TurnComparison {
+                index: i,
+                left_prompt: prompt(l),
+                right_prompt: prompt(r),
+                left_tools: tools(l),
+                right_tools: tools(r),
+                left_tokens: tokens(l),
+                right_tokens: tokens(r),
+            }
+        })
+        .collect()
+}
+
+/// Appends a step pairing `left` and `right`, either of which may be missing.
+fn push_step(
+    steps: &mut Vec<ToolStep>,
+    left: Option<&ToolCallSummary>,
+    right: Option<&ToolCallSummary>,
+) {
+    let kind = match (left, right) {
+        (Some(l), Some(r)) if l.target == r.target => StepKind::Same,
+        (Some(_), Some(_)) => StepKind::Changed,
+        (Some(_), None) => StepKind::LeftOnly,
+        _ => StepKind::RightOnly,
+    };
+    steps.push(ToolStep {
+        kind,
+        left: left.cloned(),
+        right: right.cloned(),
+    });
+}
+
+/// Length of the longest common tool name subsequence of `left` and each
+/// prefix of `right` (each suffix of both when `reverse` is set), indexed
+/// by the prefix length.
+fn lcs_lengths(left: &[ToolCallSummary], right: &[ToolCallSummary], reverse: bool) -> Vec<u32> {
+    let at = |calls: &[ToolCallSummary], i: usize| {
+        if reverse {
+            calls.len() - 1 - i
+        } else {
+            i
+        }
+    };
+    let mut row = vec![0u32; right.len() + 1];
+    for i in 0..left.len() {
+        let name = &left[at(left, i)].name;
+        let mut diagonal = 0;
+        for j in 0..right.len() {
+            let above = row[j + 1];
+            row[j + 1] = if *name == right[at(right, j)].name {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Hirschberg's algorithm: the same alignment as a full LCS table, in
+/// space linear in the length of `right`.
+fn align_into(steps: &mut Vec<ToolStep>, left: &[ToolCallSummary], right: &[ToolCallSummary]) {
+    if left.is_empty() || right.is_empty() {
+        left.iter().for_each(|l| push_step(steps, Some(l), None));
+        right.iter().for_each(|r| push_step(steps, None, Some(r)));
+        return;
+    }
+    if left.len() == 1 {
+        match right.iter().position(|r| r.name == left[0].name) {
+            Some(j) => {
+                right[..j]
+                    .iter()
+                    .for_each(|r| push_step(steps, None, Some(r)));
+                push_step(steps, Some(&left[0]), Some(&right[j]));
+                right[j + 1..]
+                    .iter()
+                    .for_each(|r| push_step(steps, None, Some(r)));
+            }
+            None => {
+                push_step(steps, Some(&left[0]), None);
+                right.iter().for_each(|r| push_step(steps, None, Some(r)));
+            }
+        }
+        return;
+    }
+
+    let mid = left.len() / 2;
+    let forward = lcs_lengths(&left[..mid], right, false);
+    let backward = lcs_lengths(&left[mid..], right, true);
+    let split = (0..=right.len())
+        .max_by_key(|&k| (forward[k] + backward[right.len() - k], std::cmp::Reverse(k)))
+        .unwrap_or(0);
+    align_into(steps, &left[..mid], &right[..split]);
+    align_into(steps, &left[mid..], &right[split..]);
+}
+
+/// Aligns the two tool call sequences by tool name using the longest common
+/// subsequence, so inserted or skipped steps don't shift everything after.
+fn align_tools(left: &[ToolCallSummary], right: &[ToolCallSummary]) -> Vec<ToolStep> {
+    let mut steps = Vec::with_capacity(left.len().max(right.len()));
+    align_into(&mut steps, left, right);
+    steps
+}
+
+pub fn compare_sessions(
+    left_id: &str,
+    left: &[ConversationMessage],
+    right_id: &str,
+    right: &[ConversationMessage],
+) -> SessionComparison {
+    let left_side = build_side(left_id, left);
+    let right_side = build_side(right_id, right);
+
+    let left_calls: Vec<ToolCallSummary> = tool_calls(left).iter().map(summarize_call).collect();
+    let right_calls: Vec<ToolCallSummary> = tool_calls(right).iter().map(summarize_call).collect();
+
+    let left_files: BTreeSet<&String> = left_side.files_touched.iter().collect();
+    let right_files: BTreeSet<&String> = right_side.files_touched.iter().collect();
+
+    SessionComparison {
+        turns: compare_turns(left, right),
+        tool_steps: align_tools(&left_calls, &right_calls),
+        files_only_left: left_files
+            .difference(&right_files)
+            .map(|f| f.to_string())
+            .collect(),
+        files_only_right: right_files
+            .difference(&left_files)
+            .map(|f| f.to_string())
+            .collect(),
+        files_in_both: left_files
+            .intersection(&right_files)
+            .map(|f| f.to_string())
+            .collect(),
+        left: left_side,
+        right: right_side,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(names: &str) -> Vec<ToolCallSummary> {
+        names
+            .chars()
+            .map(|c| ToolCallSummary {
+                name: c.to_string(),
+                target: None,
+                is_error: false,
+            })
+            .collect()
+    }
+
+    fn full_table_lcs(a: &str, b: &str) -> usize {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                table[i + 1][j + 1] = if a[i] == b[j] {
+                    table[i][j] + 1
+                } else {
+                    table[i][j + 1].max(table[i + 1][j])
+                };
+            }
+        }
+        table[a.len()][b.len()]
+    }
+
+    #[test]
+    fn aligns_to_a_longest_common_subsequence() {
+        let cases = [
+            ("", ""),
+            ("ABC", ""),
+            ("", "ABC"),
+            ("A", "BAB"),
+            ("A", "BCD"),
+            ("ABCBDAB", "BDCABA"),
+            ("RRGRBGRE", "RGEBRRGB"),
+            ("AAAA", "AA"),
+        ];
+        for (a, b) in cases {
+            let steps = align_tools(&calls(a), &calls(b));
+            let side = |pick: fn(&ToolStep) -> Option<&ToolCallSummary>| -> String {
+                steps
+                    .iter()
+                    .filter_map(pick)
+                    .map(|c| c.name.as_str())
+                    .collect()
+            };
+            assert_eq!(side(|s| s.left.as_ref()), a);
+            assert_eq!(side(|s| s.right.as_ref()), b);
+            let matched = steps.iter().filter(|s| s.kind == StepKind::Same).count();
+            assert_eq!(matched, full_table_lcs(a, b), "{a} vs {b}");
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::compaction::is_compact_summary;
+use crate::line_index::tool_result_text;
+use crate::stats::{
+    classify_user_message, content_blocks, message_text, tool_calls, unique_responses,
+    SYNTHETIC_MODEL,
+};
+use crate::types::{ContentBlock, ConversationMessage, UserMessageKind};
+
+const DEFAULT_JUMPS: usize = 10;
+/// Contributors listed per jump.
+const JUMP_CAUSES: usize = 3;
+
+/// Context size of one API response.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextPoint {
+    pub turn: usize,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub model: Option<String>,
+    /// Input, cache read and cache creation tokens the request was sent with.
+    pub context_tokens: u64,
+    pub output_tokens: u64,
+    /// Change since the previous response; negative after a compaction.
+    pub delta: i64,
+    /// Whether the conversation was compacted since the previous response.
+    pub after_compaction: bool,
+}
+
+/// Something added to the conversation between two responses.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextContributor {
+    pub kind: UserMessageKind,
+    pub uuid: Option<String>,
+    /// For tool results, the tool that produced them.
+    pub tool: Option<String>,
+    pub target: Option<String>,
+    pub tool_use_id: Option<String>,
+    pub chars: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextJump {
+    pub turn: usize,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub delta: i64,
+    pub context_tokens: u64,
+    /// Largest content added before the response, biggest first. The
+    /// previous response's own output also counts towards the jump.
+    pub causes: Vec<ContextContributor>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextGrowth {
+    pub points: Vec<ContextPoint>,
+    /// Largest increases first.
+    pub largest_jumps: Vec<ContextJump>,
+    pub peak_tokens: u64,
+    pub compactions: usize,
+}
+
+fn user_contributors(
+    msg: &ConversationMessage,
+    calls: &HashMap<&str, (&str, Option<String>)>,
+) -> Vec<ContextContributor> {
+    let kind = match classify_user_message(msg) {
+        Some(kind) => kind,
+        None => return Vec::new(),
+    };
+    if kind != UserMessageKind::ToolResult {
+        return vec![ContextContributor {
+            kind,
+            uuid: msg.uuid.clone(),
+            tool: None,
+            target: None,
+            tool_use_id: None,
+            chars: message_text(msg).chars().count(),
+        }];
+    }
+
+    content_blocks(msg)
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                ..
+            } => {
+                let call = tool_use_id.as_deref().and_then(|id| calls.get(id));
+                Some(ContextContributor {
+                    kind,
+                    uuid: msg.uuid.clone(),
+                    tool: call.map(|(name, _)| name.to_string()),
+                    target: call.and_then(|(_, target)| target.clone()),
+                    tool_use_id: tool_use_id.clone(),
+                    chars: content
+                        .as_ref()
+                        .map(|c| tool_result_text(c).chars().count())
+                        .unwrap_or(0),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Context size at every response of the main conversation, and the
+/// responses whose context grew the most.
+pub fn context_growth(records: &[ConversationMessage], jumps: Option<usize>) -> ContextGrowth {
+    let calls: HashMap<&str, (&str, Option<String>)> = tool_calls(records)
+        .iter()
+        .filter_map(|call| {
+            let target = call.tool().target().map(str::to_string);
+            call.id.map(|id| (id, (call.name, target)))
+        })
+        .collect();
+
+    let mut growth = ContextGrowth::default();
+    let mut jumps_found = Vec::new();
+    let mut pending: Vec<ContextContributor> = Vec::new();
+    let mut compacted = false;
+
+    // Later lines of a multi-block response repeat the usage
+    for msg in unique_responses(records) {
+        // Subagents run in their own context window
+        if msg.is_sidechain == Some(true) {
+            continue;
+        }
+        if msg.msg_type == "system" && msg.subtype.as_deref() == Some("compact_boundary") {
+            growth.compactions += !compacted as usize;
+            compacted = true;
+            continue;
+        }
+        if msg.msg_type == "user" {
+            if is_compact_summary(msg) {
+                growth.compactions += !compacted as usize;
+                compacted = true;
+                pending.clear();
+            } else {
+                pending.extend(user_contributors(msg, &calls));
+            }
+            continue;
+        }
+        if msg.msg_type != "assistant" || msg.is_api_error_message == Some(true) {
+            continue;
+        }
+
+        let content = match msg.message.as_ref() {
+            Some(content) if content.model.as_deref() != Some(SYNTHETIC_MODEL) => content,
+            _ => continue,
+        };
+        let usage = match content.usage.as_ref() {
+            Some(usage) => usage,
+            None => continue,
+        };
+
+        let context_tokens = usage.context_size();
+        let previous = growth.points.last().map(|p| p.context_tokens);
+        let delta = previous.map_or(0, |p| context_tokens as i64 - p as i64);
+        let turn = growth.points.len();
+
+        if delta > 0 && !compacted {
+            pending.sort_by_key(|c| Reverse(c.chars));
+            pending.truncate(JUMP_CAUSES);
+            jumps_found.push(ContextJump {
+                turn,
+                uuid: msg.uuid.clone(),
+                timestamp: msg.timestamp.clone(),
+                delta,
+                context_tokens,
+                causes: std::mem::take(&mut pending),
+            });
+        }
+        pending.clear();
+
+        growth.peak_tokens = growth.peak_tokens.max(context_tokens);
+        growth.points.push(ContextPoint {
+            turn,
+            uuid: msg.uuid.clone(),
+            timestamp: msg.timestamp.clone(),
+            model: content.model.clone(),
+            context_tokens,
+            output_tokens: usage.output_tokens.unwrap_or(0),
+            delta,
+            after_compaction: compacted,
+        });
+        compacted = false;
+    }
+
+    jumps_found.sort_by_key(|j| Reverse(j.delta));
+    jumps_found.truncate(jumps.unwrap_or(DEFAULT_JUMPS));
+    growth.largest_jumps = jumps_found;
+    growth
+}
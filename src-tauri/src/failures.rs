@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::line_index::tool_result_text;
+use crate::stats::{content_blocks, message_text, tool_calls, ToolCall};
+use crate::types::{ContentBlock, ConversationMessage};
+
+const ERROR_PREVIEW_CHARS: usize = 500;
+
+/// Marker Claude Code writes as a user message when a turn is cancelled.
+const INTERRUPTED_MARKER: &str = "[Request interrupted by user";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FailureKind {
+    ToolError,
+    ApiError,
+    Interrupted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedToolUse {
+    pub tool_use_id: Option<String>,
+    pub name: String,
+    pub target: Option<String>,
+    /// The assistant message that made the call.
+    pub uuid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureEvent {
+    pub kind: FailureKind,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub message: String,
+    pub tool_use: Option<FailedToolUse>,
+    pub retry_attempt: Option<u32>,
+    pub max_retries: Option<u32>,
+}
+
+/// Consecutive failures of the same operation: API requests Claude Code
+/// retried, or a tool called again with the same target after failing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrySequence {
+    pub kind: FailureKind,
+    pub tool: Option<String>,
+    pub target: Option<String>,
+    pub failed_attempts: usize,
+    /// Whether an attempt eventually succeeded.
+    pub recovered: bool,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureTimeline {
+    pub events: Vec<FailureEvent>,
+    pub retry_sequences: Vec<RetrySequence>,
+    pub tool_errors: usize,
+    pub api_errors: usize,
+    pub interruptions: usize,
+}
+
+fn preview(text: &str) -> String {
+    text.trim().chars().take(ERROR_PREVIEW_CHARS).collect()
+}
+
+fn api_error_text(msg: &ConversationMessage) -> String {
+    let from_error = msg.error.as_ref().map(|error| {
+        error
+            .pointer("/error/message")
+            .or_else(|| error.get("message"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string())
+    });
+    from_error
+        .or_else(|| msg.content.clone())
+        .unwrap_or_else(|| message_text(msg))
+}
+
+fn is_api_error(msg: &ConversationMessage) -> bool {
+    (msg.msg_type == "system" && msg.subtype.as_deref() == Some("api_error"))
+        || (msg.msg_type == "assistant" && msg.is_api_error_message == Some(true))
+}
+
+fn failed_tool_use(call: &ToolCall) -> FailedToolUse {
+    FailedToolUse {
+        tool_use_id: call.id.map(str::to_string),
+        name: call.name.to_string(),
+        target: call.tool().target().map(str::to_string),
+        uuid: call.message.uuid.clone(),
+    }
+}
+
+fn tool_retry_sequences(calls: &[ToolCall]) -> Vec<RetrySequence> {
+    let key = |call: &ToolCall| {
+        (
+            call.name.to_string(),
+            call.tool().target().map(str::to_string),
+        )
+    };
+    let timestamp = |call: &ToolCall| call.message.timestamp.clone();
+
+    let mut sequences = Vec::new();
+    let mut i = 0;
+    while i < calls.len() {
+        if !calls[i].is_error() {
+            i += 1;
+            continue;
+        }
+        let (name, target) = key(&calls[i]);
+        let mut end = i + 1;
+        while end < calls.len()
+            && calls[end - 1].is_error()
+            && key(&calls[end]) == (name.clone(), target.clone())
+        {
+            end += 1;
+        }
+        if end - i >= 2 {
+            let attempts = &calls[i..end];
+            sequences.push(RetrySequence {
+                kind: FailureKind::ToolError,
+                tool: Some(name),
+                target,
+                failed_attempts: attempts.iter().filter(|c| c.is_error()).count(),
+                recovered: !attempts[attempts.len() - 1].is_error(),
+                started_at: timestamp(&attempts[0]),
+                ended_at: timestamp(&attempts[attempts.len() - 1]),
+            });
+        }
+        i = end;
+    }
+    sequences
+}
+
+/// Collects tool errors, API errors and interruptions in transcript order.
+pub fn failure_timeline(records: &[ConversationMessage]) -> FailureTimeline {
+    let calls = tool_calls(records);
+    let calls_by_id: HashMap<&str, &ToolCall> = calls
+        .iter()
+        .filter_map(|call| call.id.map(|id| (id, call)))
+        .collect();
+
+    let mut timeline = FailureTimeline::default();
+    let mut api_retries: Option<RetrySequence> = None;
+
+    for msg in records {
+        if is_api_error(msg) {
+            timeline.api_errors += 1;
+            timeline.events.push(FailureEvent {
+                kind: FailureKind::ApiError,
+                uuid: msg.uuid.clone(),
+                timestamp: msg.timestamp.clone(),
+                message: preview(&api_error_text(msg)),
+                tool_use: None,
+                retry_attempt: msg.retry_attempt,
+                max_retries: msg.max_retries,
+            });
+            let sequence = api_retries.get_or_insert_with(|| RetrySequence {
+                kind: FailureKind::ApiError,
+                tool: None,
+                target: None,
+                failed_attempts: 0,
+                recovered: false,
+                started_at: msg.timestamp.clone(),
+                ended_at: None,
+            });
+            sequence.failed_attempts += 1;
+            sequence.ended_at = msg.timestamp.clone();
+            continue;
+        }
+
+        match msg.msg_type.as_str() {
+            "assistant" => {
+                if let Some(mut sequence) = api_retries.take() {
+                    sequence.recovered = true;
+                    sequence.ended_at = msg.timestamp.clone();
+                    timeline.retry_sequences.push(sequence);
+                }
+            }
+            "user" => {
+                for block in content_blocks(msg) {
+                    if let ContentBlock::ToolResult {
+                        tool_use_id,
+                        content,
+                        is_error: Some(true),
+                        ..
+                    } = block
+                    {
+                        timeline.tool_errors += 1;
+                        timeline.events.push(FailureEvent {
+                            kind: FailureKind::ToolError,
+                            uuid: msg.uuid.clone(),
+                            timestamp: msg.timestamp.clone(),
+                            message: preview(
+                                &content.as_ref().map(tool_result_text).unwrap_or_default(),
+                            ),
+                            tool_use: tool_use_id
+                                .as_deref()
+                                .and_then(|id| calls_by_id.get(id))
+                                .map(|call| failed_tool_use(call)),
+                            retry_attempt: None,
+                            max_retries: None,
+                        });
+                    }
+                }
+
+                let text = message_text(msg);
+                if text.trim_start().starts_with(INTERRUPTED_MARKER) {
+                    timeline.interruptions += 1;
+                    timeline.events.push(FailureEvent {
+                        kind: FailureKind::Interrupted,
+                        uuid: msg.uuid.clone(),
+                        timestamp: msg.timestamp.clone(),
+                        message: preview(&text),
+                        tool_use: None,
+                        retry_attempt: None,
+                        max_retries: None,
+                    });
+                    if let Some(sequence) = api_retries.take() {
+                        timeline.retry_sequences.push(sequence);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    timeline.retry_sequences.extend(api_retries);
+    timeline
+        .retry_sequences
+        .extend(tool_retry_sequences(&calls));
+    timeline
+        .retry_sequences
+        .sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    timeline
+}
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use git2::{BranchType, Repository, Sort};
+use serde::Serialize;
+
+use crate::stats::timestamp_millis;
+use crate::types::ConversationMessage;
+
+/// Commits made shortly after the last message (e.g. a manual commit once
+/// Claude finished) still count towards the session.
+const COMMIT_GRACE_MS: i64 = 10 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchSpan {
+    pub branch: String,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+    pub message_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommit {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub timestamp: i64,
+    pub branches: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitContext {
+    pub cwd: Option<String>,
+    pub repo_root: Option<String>,
+    pub branches: Vec<BranchSpan>,
+    pub commits: Vec<GitCommit>,
+}
+
+/// Collapses the `gitBranch` recorded on each message into consecutive spans,
+/// so switching away from a branch and back shows up as separate entries.
+pub fn branch_history(messages: &[ConversationMessage]) -> Vec<BranchSpan> {
+    let mut spans: Vec<BranchSpan> = Vec::new();
+
+    for msg in messages {
+        let branch = match msg.git_branch.as_deref() {
+            Some(b) if !b.is_empty() => b,
+            _ => continue,
+        };
+
+        match spans.last_mut() {
+            Some(span) if span.branch == branch => {
+                span.message_count += 1;
+                if msg.timestamp.is_some() {
+                    span.last_seen = msg.timestamp.clone();
+                }
+            }
+            _ => spans.push(BranchSpan {
+                branch: branch.to_string(),
+                first_seen: msg.timestamp.clone(),
+                last_seen: msg.timestamp.clone(),
+                message_count: 1,
+            }),
+        }
+    }
+
+    spans
+}
+
+/// The first and last message times of a session, in epoch milliseconds.
+pub fn session_window(messages: &[ConversationMessage]) -> Option<(i64, i64)> {
+    let mut times = messages
+        .iter()
+        .filter_map(|m| m.timestamp.as_deref().and_then(timestamp_millis));
+    let first = times.next()?;
+    Some(times.fold((first, first), |(lo, hi), t| (lo.min(t), hi.max(t))))
+}
+
+/// Opens the repository containing `path` and lists commits on local branches
+/// whose commit time falls inside `from..=to`. Reads the local repository
+/// only; nothing is fetched.
+pub fn commits_in_window(path: &Path, from: i64, to: i64) -> Result<(String, Vec<GitCommit>)> {
+    let repo = Repository::discover(path)?;
+    let root = repo
+        .workdir()
+        .unwrap_or_else(|| repo.path())
+        .to_string_lossy()
+        .trim_end_matches('/')
+        .to_string();
+
+    let mut heads = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        if let (Some(name), Some(target)) = (branch.name()?, branch.get().target()) {
+            heads.push((name.to_string(), target));
+        }
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TIME)?;
+    if heads.is_empty() {
+        walk.push_head()?;
+    }
+    for (_, target) in &heads {
+        walk.push(*target)?;
+    }
+
+    let until = to + COMMIT_GRACE_MS;
+    let mut commits = Vec::new();
+    let mut seen = HashSet::new();
+
+    for oid in walk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        let time = commit.time().seconds() * 1000;
+        if time < from {
+            // Sorted newest first, so everything after is older still
+            break;
+        }
+        if time > until || !seen.insert(oid) {
+            continue;
+        }
+
+        let branches = heads
+            .iter()
+            .filter(|(_, head)| {
+                *head == oid || repo.graph_descendant_of(*head, oid).unwrap_or(false)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        commits.push(GitCommit {
+            id: oid.to_string(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            timestamp: time,
+            branches,
+        });
+    }
+
+    commits.reverse();
+    Ok((root, commits))
+}
+
+pub fn build_git_context(messages: &[ConversationMessage], include_commits: bool) -> GitContext {
+    let cwd = messages.iter().find_map(|m| m.cwd.clone());
+    let mut context = GitContext {
+        cwd: cwd.clone(),
+        branches: branch_history(messages),
+        ..Default::default()
+    };
+
+    if !include_commits {
+        return context;
+    }
+
+    if let (Some(cwd), Some((from, to))) = (cwd, session_window(messages)) {
+        match commits_in_window(Path::new(&cwd), from, to) {
+            Ok((root, commits)) => {
+                context.repo_root = Some(root);
+                context.commits = commits;
+            }
+            Err(e) => log::warn!("No git history for {}: {}", cwd, e),
+        }
+    }
+
+    context
+}
@@ -0,0 +1,549 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::line_index::tool_result_text;
+use crate::stats::{classify_user_message, content_blocks, message_text};
+use crate::storage::Storage;
+use crate::types::{ContentBlock, ConversationMessage, UserMessageKind};
+
+const OUTPUT_PREVIEW_CHARS: usize = 2000;
+
+const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashCommandEvent {
+    /// Including the leading slash, e.g. `/review`.
+    pub name: String,
+    pub args: Option<String>,
+    /// What a local command such as `/cost` printed.
+    pub output: Option<String>,
+    pub is_error: bool,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookStatus {
+    Success,
+    NonBlockingError,
+    /// The hook stopped the tool call or turn and fed its output to Claude.
+    BlockingError,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookEvent {
+    /// e.g. `PostToolUse`.
+    pub event: String,
+    /// The event with its matcher, e.g. `PostToolUse:Edit`.
+    pub name: String,
+    pub command: Option<String>,
+    pub status: HookStatus,
+    pub exit_code: Option<i32>,
+    pub output: Option<String>,
+    pub tool_use_id: Option<String>,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInvocations {
+    pub commands: Vec<SlashCommandEvent>,
+    pub hooks: Vec<HookEvent>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashCommandStats {
+    pub name: String,
+    pub uses: usize,
+    pub errors: usize,
+    pub sessions: usize,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookStats {
+    pub name: String,
+    pub event: String,
+    pub command: Option<String>,
+    pub runs: usize,
+    /// Runs that did not complete successfully, including blocking ones.
+    pub failures: usize,
+    pub blocked: usize,
+    pub sessions: usize,
+    pub last_run_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvocationReport {
+    /// Most used first.
+    pub commands: Vec<SlashCommandStats>,
+    pub hooks: Vec<HookStats>,
+}
+
+/// Hook details shared by the different places Claude Code records them.
+struct ParsedHook {
+    name: String,
+    command: Option<String>,
+    status: HookStatus,
+    exit_code: Option<i32>,
+    output: Option<String>,
+}
+
+impl ParsedHook {
+    fn into_event(self, msg: &ConversationMessage, tool_use_id: Option<String>) -> HookEvent {
+        HookEvent {
+            event: hook_event_name(&self.name).to_string(),
+            name: self.name,
+            command: self.command,
+            status: self.status,
+            exit_code: self.exit_code,
+            output: self.output,
+            tool_use_id,
+            uuid: msg.uuid.clone(),
+            timestamp: msg.timestamp.clone(),
+        }
+    }
+}
+
+fn hook_event_name(name: &str) -> &str {
+    name.split(':').next().unwrap_or(name)
+}
+
+fn is_hook_name(name: &str) -> bool {
+    HOOK_EVENTS.contains(&hook_event_name(name))
+}
+
+fn preview(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.chars().take(OUTPUT_PREVIEW_CHARS).collect())
+}
+
+/// Hook messages are written for the terminal and may carry ANSI styling.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip `ESC [ params letter`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// The text between `<tag>` and `</tag>`.
+fn tag_content<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find(&close)? + start;
+    Some(text[start..end].trim())
+}
+
+/// Parses hook status lines such as
+/// `PostToolUse:Edit [prettier --write] completed successfully` or
+/// `PreToolUse:Bash [./check.sh] failed with non-blocking status code 1: …`.
+fn parse_hook_status(text: &str) -> Option<ParsedHook> {
+    let text = strip_ansi(text);
+    let (name, rest) = text.trim().split_once(" [")?;
+    if !is_hook_name(name) {
+        return None;
+    }
+    let (command, rest) = rest.split_once("] ")?;
+
+    let status = if rest.starts_with("completed successfully") {
+        HookStatus::Success
+    } else if rest.contains("non-blocking status code") {
+        HookStatus::NonBlockingError
+    } else if rest.starts_with("failed") {
+        HookStatus::BlockingError
+    } else if rest.starts_with("cancelled") {
+        HookStatus::Cancelled
+    } else {
+        return None;
+    };
+    let exit_code = match status {
+        HookStatus::Success => Some(0),
+        _ => rest
+            .split_once("status code ")
+            .and_then(|(_, code)| code.split(|c: char| !c.is_ascii_digit()).next())
+            .and_then(|code| code.parse().ok()),
+    };
+
+    Some(ParsedHook {
+        name: name.to_string(),
+        command: Some(command.to_string()),
+        status,
+        exit_code,
+        output: rest
+            .split_once(": ")
+            .and_then(|(_, output)| preview(output)),
+    })
+}
+
+/// Parses the feedback of a blocking hook, which Claude Code hands to the
+/// model as `Stop hook feedback: …` or `PreToolUse:Bash hook error: …`,
+/// usually followed by `[command]: output`.
+fn parse_hook_feedback(text: &str) -> Option<ParsedHook> {
+    let text = strip_ansi(text);
+    let text = text.trim();
+    let (name, body) = [" hook feedback:", " hook error:"]
+        .iter()
+        .find_map(|marker| text.split_once(marker))?;
+    if !is_hook_name(name) {
+        return None;
+    }
+    let body = body.trim();
+    let (command, output) = match body.strip_prefix('[').and_then(|b| b.split_once("]: ")) {
+        Some((command, output)) => (Some(command.to_string()), output),
+        None => (None, body),
+    };
+
+    Some(ParsedHook {
+        name: name.to_string(),
+        command,
+        status: HookStatus::BlockingError,
+        exit_code: None,
+        output: preview(output),
+    })
+}
+
+/// Parses `hook_*` attachment records.
+fn parse_hook_attachment(attachment: &Value) -> Option<ParsedHook> {
+    let status = match attachment.get("type")?.as_str()? {
+        "hook_success" => HookStatus::Success,
+        "hook_non_blocking_error" => HookStatus::NonBlockingError,
+        "hook_blocking_error" => HookStatus::BlockingError,
+        "hook_cancelled" => HookStatus::Cancelled,
+        _ => return None,
+    };
+    let str_field = |pointer: &str| {
+        attachment
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .filter(|s| !s.trim().is_empty())
+    };
+    let name = str_field("/hookName").or(str_field("/hookEvent"))?;
+    let output = match status {
+        HookStatus::Success => str_field("/stdout").or(str_field("/content")),
+        _ => str_field("/stderr")
+            .or(str_field("/blockingError/blockingError"))
+            .or(str_field("/content"))
+            .or(str_field("/stdout")),
+    };
+
+    Some(ParsedHook {
+        name: name.to_string(),
+        command: str_field("/command")
+            .or(str_field("/blockingError/command"))
+            .map(str::to_string),
+        status,
+        exit_code: attachment
+            .get("exitCode")
+            .and_then(Value::as_i64)
+            .map(|code| code as i32),
+        output: output.and_then(preview),
+    })
+}
+
+fn user_message_hooks(msg: &ConversationMessage, hooks: &mut Vec<HookEvent>) {
+    for block in content_blocks(msg) {
+        if let ContentBlock::ToolResult {
+            tool_use_id,
+            content: Some(content),
+            is_error: Some(true),
+            ..
+        } = block
+        {
+            let text = tool_result_text(content);
+            if let Some(hook) = parse_hook_feedback(&text).or_else(|| parse_hook_status(&text)) {
+                hooks.push(hook.into_event(msg, tool_use_id.clone()));
+            }
+        }
+    }
+
+    if classify_user_message(msg) != Some(UserMessageKind::HookFeedback) {
+        return;
+    }
+    let text = message_text(msg);
+    let hook = match tag_content(&text, "user-prompt-submit-hook") {
+        Some(output) => Some(ParsedHook {
+            name: "UserPromptSubmit".to_string(),
+            command: None,
+            status: HookStatus::Success,
+            exit_code: Some(0),
+            output: preview(output),
+        }),
+        None => parse_hook_feedback(&text),
+    };
+    hooks.extend(hook.map(|hook| hook.into_event(msg, None)));
+}
+
+/// Slash commands the user ran and hooks that fired, in transcript order.
+pub fn session_invocations(records: &[ConversationMessage]) -> SessionInvocations {
+    let mut invocations = SessionInvocations::default();
+    // Local command output follows the command it belongs to
+    let mut awaiting_output: Option<usize> = None;
+
+    for msg in records {
+        match msg.msg_type.as_str() {
+            "system" => {
+                if let Some(hook) = msg.content.as_deref().and_then(parse_hook_status) {
+                    invocations
+                        .hooks
+                        .push(hook.into_event(msg, msg.tool_use_id.clone()));
+                }
+            }
+            "attachment" => {
+                if let Some(hook) = msg.attachment.as_ref().and_then(parse_hook_attachment) {
+                    invocations
+                        .hooks
+                        .push(hook.into_event(msg, msg.tool_use_id.clone()));
+                }
+            }
+            "user" => match classify_user_message(msg) {
+                Some(UserMessageKind::SlashCommand) => {
+                    let text = message_text(msg);
+                    let name = match tag_content(&text, "command-name") {
+                        Some(name) => name,
+                        None => continue,
+                    };
+                    invocations.commands.push(SlashCommandEvent {
+                        name: name.to_string(),
+                        args: tag_content(&text, "command-args")
+                            .filter(|a| !a.is_empty())
+                            .map(str::to_string),
+                        output: None,
+                        is_error: false,
+                        uuid: msg.uuid.clone(),
+                        timestamp: msg.timestamp.clone(),
+                    });
+                    awaiting_output = Some(invocations.commands.len() - 1);
+                }
+                Some(UserMessageKind::LocalCommandOutput) => {
+                    let text = message_text(msg);
+                    let command = match awaiting_output.take() {
+                        Some(i) => &mut invocations.commands[i],
+                        None => continue,
+                    };
+                    if let Some(stderr) = tag_content(&text, "local-command-stderr") {
+                        command.is_error = !stderr.is_empty();
+                        command.output = preview(stderr);
+                    } else if let Some(stdout) = tag_content(&text, "local-command-stdout") {
+                        command.output = preview(stdout);
+                    }
+                }
+                Some(UserMessageKind::Meta) => {}
+                _ => {
+                    awaiting_output = None;
+                    user_message_hooks(msg, &mut invocations.hooks);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    invocations
+}
+
+fn update_last(last: &mut Option<String>, timestamp: &Option<String>) {
+    if timestamp.is_some() && *timestamp > *last {
+        *last = timestamp.clone();
+    }
+}
+
+/// Slash command and hook usage across sessions, optionally limited to a
+/// project (path or id).
+pub async fn collect_invocation_report(
+    storage: &Storage,
+    project: Option<&str>,
+) -> Result<InvocationReport> {
+    let mut commands: HashMap<String, SlashCommandStats> = HashMap::new();
+    let mut hooks: HashMap<(String, Option<String>), HookStats> = HashMap::new();
+
+    storage
+        .for_each_session(project, |_, records| {
+            let invocations = session_invocations(records);
+
+            let mut seen_commands = HashSet::new();
+            for event in &invocations.commands {
+                let stats =
+                    commands
+                        .entry(event.name.clone())
+                        .or_insert_with(|| SlashCommandStats {
+                            name: event.name.clone(),
+                            uses: 0,
+                            errors: 0,
+                            sessions: 0,
+                            last_used_at: None,
+                        });
+                stats.uses += 1;
+                stats.errors += event.is_error as usize;
+                if seen_commands.insert(&event.name) {
+                    stats.sessions += 1;
+                }
+                update_last(&mut stats.last_used_at, &event.timestamp);
+            }
+
+            let mut seen_hooks = HashSet::new();
+            for event in &invocations.hooks {
+                let key = (event.name.clone(), event.command.clone());
+                let stats = hooks.entry(key.clone()).or_insert_with(|| HookStats {
+                    name: event.name.clone(),
+                    event: event.event.clone(),
+                    command: event.command.clone(),
+                    runs: 0,
+                    failures: 0,
+                    blocked: 0,
+                    sessions: 0,
+                    last_run_at: None,
+                });
+                stats.runs += 1;
+                stats.failures += (event.status != HookStatus::Success) as usize;
+                stats.blocked += (event.status == HookStatus::BlockingError) as usize;
+                if seen_hooks.insert(key) {
+                    stats.sessions += 1;
+                }
+                update_last(&mut stats.last_run_at, &event.timestamp);
+            }
+        })
+        .await?;
+
+    let mut report = InvocationReport {
+        commands: commands.into_values().collect(),
+        hooks: hooks.into_values().collect(),
+    };
+    report
+        .commands
+        .sort_by(|a, b| b.uses.cmp(&a.uses).then_with(|| a.name.cmp(&b.name)));
+    report
+        .hooks
+        .sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.name.cmp(&b.name)));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_hook_status_lines() {
+        let hook = parse_hook_status("PostToolUse:Edit [prettier --write] completed successfully")
+            .unwrap();
+        assert_eq!(hook.name, "PostToolUse:Edit");
+        assert_eq!(hook.command.as_deref(), Some("prettier --write"));
+        assert_eq!(hook.status, HookStatus::Success);
+        assert_eq!(hook.exit_code, Some(0));
+        assert_eq!(hook.output, None);
+
+        let hook = parse_hook_status(
+            "PreToolUse:Bash [./check.sh] failed with non-blocking status code 1: lint: 2 errors",
+        )
+        .unwrap();
+        assert_eq!(hook.status, HookStatus::NonBlockingError);
+        assert_eq!(hook.exit_code, Some(1));
+        assert_eq!(hook.output.as_deref(), Some("lint: 2 errors"));
+
+        assert!(parse_hook_status("Build [make] completed successfully").is_none());
+        assert!(parse_hook_status("PostToolUse:Edit [prettier] is running").is_none());
+    }
+
+    #[test]
+    fn parses_hook_feedback() {
+        let hook =
+            parse_hook_feedback("Stop hook feedback:\n[./verify.sh]: tests are failing").unwrap();
+        assert_eq!(hook.name, "Stop");
+        assert_eq!(hook.command.as_deref(), Some("./verify.sh"));
+        assert_eq!(hook.status, HookStatus::BlockingError);
+        assert_eq!(hook.output.as_deref(), Some("tests are failing"));
+
+        let hook = parse_hook_feedback("PreToolUse:Bash hook error: rm is not allowed").unwrap();
+        assert_eq!(hook.name, "PreToolUse:Bash");
+        assert_eq!(hook.command, None);
+        assert_eq!(hook.output.as_deref(), Some("rm is not allowed"));
+
+        assert!(parse_hook_feedback("Reviewer hook feedback: looks good").is_none());
+    }
+
+    #[test]
+    fn parses_hook_attachments() {
+        let hook = parse_hook_attachment(&json!({
+            "type": "hook_non_blocking_error",
+            "hookName": "PostToolUse:Write",
+            "command": "eslint --fix",
+            "stdout": "",
+            "stderr": "1 problem",
+            "exitCode": 1,
+        }))
+        .unwrap();
+        assert_eq!(hook.name, "PostToolUse:Write");
+        assert_eq!(hook.command.as_deref(), Some("eslint --fix"));
+        assert_eq!(hook.status, HookStatus::NonBlockingError);
+        assert_eq!(hook.exit_code, Some(1));
+        assert_eq!(hook.output.as_deref(), Some("1 problem"));
+
+        let hook = parse_hook_attachment(&json!({
+            "type": "hook_blocking_error",
+            "hookEvent": "Stop",
+            "blockingError": { "blockingError": "keep going", "command": "./verify.sh" },
+        }))
+        .unwrap();
+        assert_eq!(hook.name, "Stop");
+        assert_eq!(hook.command.as_deref(), Some("./verify.sh"));
+        assert_eq!(hook.output.as_deref(), Some("keep going"));
+
+        assert!(parse_hook_attachment(&json!({ "type": "todo_reminder" })).is_none());
+    }
+
+    #[test]
+    fn reads_tag_content() {
+        let text =
+            "<command-name>/review</command-name>\n<command-args> src/lib.rs </command-args>";
+        assert_eq!(tag_content(text, "command-name"), Some("/review"));
+        assert_eq!(tag_content(text, "command-args"), Some("src/lib.rs"));
+        assert_eq!(tag_content(text, "command-message"), None);
+        assert_eq!(tag_content("<command-args>unclosed", "command-args"), None);
+    }
+
+    #[test]
+    fn strips_ansi_styling() {
+        let text =
+            "\u{1b}[1mPostToolUse:Edit\u{1b}[22m [fmt] \u{1b}[32mcompleted successfully\u{1b}[0m";
+        assert_eq!(
+            strip_ansi(text),
+            "PostToolUse:Edit [fmt] completed successfully"
+        );
+        assert_eq!(
+            parse_hook_status(text).map(|hook| hook.status),
+            Some(HookStatus::Success)
+        );
+    }
+}
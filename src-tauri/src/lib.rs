@@ -1,5 +1,31 @@
+mod activity;
+mod audit;
+mod bash_history;
+mod cache;
+mod chains;
 mod commands;
+mod compaction;
+mod compare;
+mod context_growth;
+mod failures;
+mod git_context;
+mod invocations;
+mod line_index;
+mod mcp;
+mod models;
+mod projects;
+mod prompt_library;
+mod prompts;
+mod query;
+mod resume;
+mod stats;
 mod storage;
+mod store;
+mod summary;
+mod tags;
+mod thinking;
+mod todos;
+mod tools;
 mod types;
 mod watcher;
 
@@ -7,8 +33,11 @@ use std::sync::Arc;
 
 use tauri::Manager;
 
+use prompt_library::PromptLibrary;
+use resume::TerminalSettings;
 use storage::Storage;
-use watcher::start_watcher;
+use tags::TagStore;
+use watcher::WatcherHandle;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -20,18 +49,81 @@ pub fn run() {
             tauri::async_runtime::block_on(async {
                 storage_clone.load().await
             })?;
+            let warm_storage = storage.clone();
+            tauri::async_runtime::spawn(async move { warm_storage.warm_session_meta().await });
 
-            start_watcher(storage.clone(), app.handle().clone())?;
+            let watcher = Arc::new(WatcherHandle::new(storage.clone(), app.handle().clone()));
+            watcher.start()?;
+            let tags_path = app.path().app_data_dir()?.join("session-tags.json");
+            let tags = tauri::async_runtime::block_on(TagStore::load(tags_path))?;
+            let library_path = app.path().app_data_dir()?.join("prompt-library.json");
+            let library = tauri::async_runtime::block_on(PromptLibrary::load(library_path))?;
+            let terminal_path = app.path().app_data_dir()?.join("terminal.json");
+            let terminal = tauri::async_runtime::block_on(TerminalSettings::load(terminal_path))?;
+
+            app.manage(watcher);
             app.manage(storage);
+            app.manage(Arc::new(tags));
+            app.manage(Arc::new(library));
+            app.manage(Arc::new(terminal));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_sessions,
+            commands::query_sessions,
             commands::get_projects,
+            commands::get_project_list,
             commands::get_conversation,
+            commands::get_chain_conversation,
             commands::get_conversation_stream,
+            commands::get_conversation_window,
+            commands::get_tool_result_content,
             commands::get_session_meta,
+            commands::get_session_stats,
+            commands::get_session_summary,
+            commands::get_compaction_segments,
+            commands::get_compaction_segment,
+            commands::get_tool_calls,
+            commands::get_todo_progress,
+            commands::get_failure_timeline,
+            commands::get_thinking,
+            commands::get_thinking_report,
+            commands::get_model_breakdown,
+            commands::get_context_growth,
+            commands::get_session_cache_efficiency,
+            commands::get_cache_report,
+            commands::get_session_invocations,
+            commands::get_invocation_report,
+            commands::get_session_mcp_usage,
+            commands::get_mcp_report,
+            commands::get_session_git_context,
+            commands::get_resume_command,
+            commands::compare_sessions,
+            commands::get_activity,
+            commands::get_bash_history,
+            commands::get_default_audit_config,
+            commands::audit_session,
+            commands::get_audit_report,
+            commands::open_in_terminal,
+            commands::get_prompts,
+            commands::get_saved_prompts,
+            commands::get_prompt_tags,
+            commands::save_prompt,
+            commands::remove_saved_prompt,
+            commands::get_all_tags,
+            commands::set_session_tags,
+            commands::get_watcher_status,
+            commands::start_watcher,
+            commands::stop_watcher,
+            commands::restart_watcher,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Some(watcher) = app.try_state::<Arc<WatcherHandle>>() {
+                    watcher.stop();
+                }
+            }
+        });
 }
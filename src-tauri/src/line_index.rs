@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+
+use crate::types::{ContentBlock, ContentValue, ConversationMessage};
+
+/// Byte offsets of the displayable records in a session file, in the order
+/// `get_conversation` returns them (summaries first, latest first, then
+/// user/assistant messages in file order), so any index range can be read
+/// without parsing the whole file.
+/// The only field the index reads, so scanning skips over message content
+/// instead of building it.
+#[derive(Deserialize)]
+struct RecordType {
+    #[serde(rename = "type")]
+    msg_type: String,
+}
+
+#[derive(Debug, Default)]
+pub struct LineIndex {
+    summaries: Vec<u64>,
+    messages: Vec<u64>,
+    scanned_to: u64,
+}
+
+impl LineIndex {
+    pub fn len(&self) -> usize {
+        self.summaries.len() + self.messages.len()
+    }
+
+    pub fn offset(&self, index: usize) -> Option<u64> {
+        if index < self.summaries.len() {
+            self.summaries
+                .get(self.summaries.len() - 1 - index)
+                .copied()
+        } else {
+            self.messages.get(index - self.summaries.len()).copied()
+        }
+    }
+
+    /// Bytes of the file already covered by the index.
+    pub fn scanned_to(&self) -> u64 {
+        self.scanned_to
+    }
+
+    /// Indexes records appended since the last scan. Session files are
+    /// append-only, so a live session only pays for its new lines.
+    pub async fn extend(&mut self, path: &Path) -> Result<()> {
+        let mut file = File::open(path).await?;
+        file.seek(std::io::SeekFrom::Start(self.scanned_to)).await?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            let bytes = reader.read_line(&mut line).await?;
+            if bytes == 0 {
+                break;
+            }
+
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                match serde_json::from_str::<RecordType>(trimmed) {
+                    Ok(record) => match record.msg_type.as_str() {
+                        "user" | "assistant" => self.messages.push(self.scanned_to),
+                        "summary" => self.summaries.push(self.scanned_to),
+                        _ => {}
+                    },
+                    // A trailing line without a newline may still be mid-write
+                    Err(_) if !line.ends_with('\n') => break,
+                    Err(_) => {}
+                }
+            }
+
+            self.scanned_to += bytes as u64;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads and parses the record starting at `offset`.
+pub async fn read_record_at(path: &Path, offset: u64) -> Result<Option<ConversationMessage>> {
+    let mut file = File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    Ok(ConversationMessage::from_line(line.trim()).ok())
+}
+
+/// Replaces tool result contents longer than `max_chars` with a truncated
+/// text preview, marking the block so the UI can offer the full content.
+pub fn truncate_tool_results(msg: &mut ConversationMessage, max_chars: usize) {
+    let blocks = match msg.message.as_mut().map(|m| &mut m.content) {
+        Some(ContentValue::Blocks(blocks)) => blocks,
+        _ => return,
+    };
+
+    for block in blocks {
+        if let ContentBlock::ToolResult {
+            content: Some(content),
+            truncated,
+            ..
+        } = block
+        {
+            let text = tool_result_text(content);
+            if text.chars().count() > max_chars {
+                *content = Value::String(text.chars().take(max_chars).collect());
+                *truncated = Some(true);
+            }
+        }
+    }
+}
+
+/// Flattens tool result content, which is either a string or a list of
+/// content blocks, into its text.
+pub fn tool_result_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,131 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::line_index::tool_result_text;
+use crate::stats::{tool_calls, ToolCall};
+use crate::storage::Storage;
+use crate::tools::parse_mcp_tool_name;
+use crate::types::ConversationMessage;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolStats {
+    pub tool: String,
+    pub calls: usize,
+    pub errors: usize,
+    /// Characters of result text returned to the model.
+    pub result_chars: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerStats {
+    pub server: String,
+    pub calls: usize,
+    pub errors: usize,
+    pub result_chars: usize,
+    pub sessions: usize,
+    pub last_used_at: Option<String>,
+    /// Most called first.
+    pub tools: Vec<McpToolStats>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpReport {
+    pub total_calls: usize,
+    pub total_errors: usize,
+    /// Most called first.
+    pub servers: Vec<McpServerStats>,
+}
+
+impl McpReport {
+    fn add_call(&mut self, server: &str, tool: &str, call: &ToolCall) {
+        let index = match self.servers.iter().position(|s| s.server == server) {
+            Some(i) => i,
+            None => {
+                self.servers.push(McpServerStats {
+                    server: server.to_string(),
+                    ..Default::default()
+                });
+                self.servers.len() - 1
+            }
+        };
+        let stats = &mut self.servers[index];
+        let is_error = call.is_error() as usize;
+        let result_chars = call
+            .result
+            .as_ref()
+            .and_then(|r| r.content)
+            .map(|content| tool_result_text(content).chars().count())
+            .unwrap_or(0);
+
+        self.total_calls += 1;
+        self.total_errors += is_error;
+        stats.calls += 1;
+        stats.errors += is_error;
+        stats.result_chars += result_chars;
+        if call.message.timestamp > stats.last_used_at {
+            stats.last_used_at = call.message.timestamp.clone();
+        }
+
+        let tool_index = match stats.tools.iter().position(|t| t.tool == tool) {
+            Some(i) => i,
+            None => {
+                stats.tools.push(McpToolStats {
+                    tool: tool.to_string(),
+                    ..Default::default()
+                });
+                stats.tools.len() - 1
+            }
+        };
+        let tool_stats = &mut stats.tools[tool_index];
+        tool_stats.calls += 1;
+        tool_stats.errors += is_error;
+        tool_stats.result_chars += result_chars;
+    }
+
+    fn add_session(&mut self, records: &[ConversationMessage]) {
+        let mut servers = HashSet::new();
+        for call in tool_calls(records) {
+            if let Some((server, tool)) = parse_mcp_tool_name(call.name) {
+                self.add_call(server, tool, &call);
+                servers.insert(server);
+            }
+        }
+        for stats in &mut self.servers {
+            if servers.contains(stats.server.as_str()) {
+                stats.sessions += 1;
+            }
+        }
+    }
+
+    fn sort(&mut self) {
+        self.servers.sort_by_key(|s| Reverse(s.calls));
+        for stats in &mut self.servers {
+            stats.tools.sort_by_key(|t| Reverse(t.calls));
+        }
+    }
+}
+
+/// MCP calls in one session, per server and tool.
+pub fn session_mcp_usage(records: &[ConversationMessage]) -> McpReport {
+    let mut report = McpReport::default();
+    report.add_session(records);
+    report.sort();
+    report
+}
+
+/// MCP calls across sessions, optionally limited to a project (path or id).
+pub async fn collect_mcp_report(storage: &Storage, project: Option<&str>) -> Result<McpReport> {
+    let mut report = McpReport::default();
+    storage
+        .for_each_session(project, |_, records| report.add_session(records))
+        .await?;
+
+    report.sort();
+    Ok(report)
+}
@@ -0,0 +1,191 @@
+use serde::Serialize;
+
+use crate::stats::{is_human_prompt, unique_responses, SYNTHETIC_MODEL};
+use crate::types::{ConversationMessage, TokenTotals};
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    pub model: String,
+    pub responses: usize,
+    pub tokens: TokenTotals,
+    pub total_tokens: u64,
+    pub first_used_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SwitchKind {
+    /// The model changed between turns, e.g. through `/model`.
+    Switch,
+    /// The model changed in the middle of a turn or right after an API
+    /// error, which is how Claude Code falls back when a model is overloaded.
+    Fallback,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSwitch {
+    pub kind: SwitchKind,
+    pub from: String,
+    pub to: String,
+    /// The first response from the new model.
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBreakdown {
+    /// Models in the order they were first used.
+    pub models: Vec<ModelUsage>,
+    pub switches: Vec<ModelSwitch>,
+}
+
+/// Responses and tokens per model, and every point where the model changed.
+pub fn model_breakdown(messages: &[ConversationMessage]) -> ModelBreakdown {
+    let mut breakdown = ModelBreakdown::default();
+    let mut current: Option<&str> = None;
+    let mut prompted_since_last_response = false;
+    let mut api_error_since_last_response = false;
+
+    // Later lines of a multi-block response repeat the model and usage
+    for msg in unique_responses(messages) {
+        // Subagents pick their own model, so they neither switch the main
+        // thread's model nor prompt it
+        let is_sidechain = msg.is_sidechain == Some(true);
+        match msg.msg_type.as_str() {
+            "user" => {
+                if is_human_prompt(msg) && !is_sidechain {
+                    prompted_since_last_response = true;
+                }
+                continue;
+            }
+            "system" => {
+                if msg.subtype.as_deref() == Some("api_error") {
+                    api_error_since_last_response = true;
+                }
+                continue;
+            }
+            "assistant" => {}
+            _ => continue,
+        }
+        if msg.is_api_error_message == Some(true) {
+            api_error_since_last_response = true;
+            continue;
+        }
+
+        let content = match msg.message.as_ref() {
+            Some(content) => content,
+            None => continue,
+        };
+        let model = match content.model.as_deref() {
+            Some(model) if model != SYNTHETIC_MODEL => model,
+            _ => continue,
+        };
+
+        if !is_sidechain {
+            if let Some(previous) = current.filter(|&p| p != model) {
+                let kind = if prompted_since_last_response && !api_error_since_last_response {
+                    SwitchKind::Switch
+                } else {
+                    SwitchKind::Fallback
+                };
+                breakdown.switches.push(ModelSwitch {
+                    kind,
+                    from: previous.to_string(),
+                    to: model.to_string(),
+                    uuid: msg.uuid.clone(),
+                    timestamp: msg.timestamp.clone(),
+                });
+            }
+            current = Some(model);
+            prompted_since_last_response = false;
+            api_error_since_last_response = false;
+        }
+
+        let index = match breakdown.models.iter().position(|m| m.model == model) {
+            Some(i) => i,
+            None => {
+                breakdown.models.push(ModelUsage {
+                    model: model.to_string(),
+                    first_used_at: msg.timestamp.clone(),
+                    ..Default::default()
+                });
+                breakdown.models.len() - 1
+            }
+        };
+        let usage = &mut breakdown.models[index];
+        usage.responses += 1;
+        usage.last_used_at = msg.timestamp.clone();
+        if let Some(ref tokens) = content.usage {
+            usage.tokens.add(tokens);
+        }
+        usage.total_tokens = usage.tokens.total();
+    }
+
+    breakdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(json: &str) -> ConversationMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn subagent_models_are_not_switches() {
+        let messages = [
+            record(
+                r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"review this"}}"#,
+            ),
+            record(
+                r#"{"type":"assistant","uuid":"a1","message":{"id":"m1","role":"assistant","model":"claude-opus-4-1","content":[]}}"#,
+            ),
+            record(
+                r#"{"type":"user","uuid":"u2","isSidechain":true,"message":{"role":"user","content":"search the repo"}}"#,
+            ),
+            record(
+                r#"{"type":"assistant","uuid":"a2","isSidechain":true,"message":{"id":"m2","role":"assistant","model":"claude-haiku-4-5","content":[]}}"#,
+            ),
+            record(
+                r#"{"type":"assistant","uuid":"a3","message":{"id":"m3","role":"assistant","model":"claude-opus-4-1","content":[]}}"#,
+            ),
+        ];
+        let breakdown = model_breakdown(&messages);
+        assert!(breakdown.switches.is_empty());
+        let models: Vec<_> = breakdown
+            .models
+            .iter()
+            .map(|m| (m.model.as_str(), m.responses))
+            .collect();
+        assert_eq!(models, [("claude-opus-4-1", 2), ("claude-haiku-4-5", 1)]);
+    }
+
+    #[test]
+    fn model_change_after_a_prompt_is_a_switch() {
+        let messages = [
+            record(
+                r#"{"type":"assistant","uuid":"a1","message":{"id":"m1","role":"assistant","model":"claude-opus-4-1","content":[]}}"#,
+            ),
+            record(
+                r#"{"type":"user","uuid":"u1","message":{"role":"user","content":"keep going"}}"#,
+            ),
+            record(
+                r#"{"type":"assistant","uuid":"a2","message":{"id":"m2","role":"assistant","model":"claude-sonnet-4-5","content":[]}}"#,
+            ),
+            record(
+                r#"{"type":"assistant","uuid":"a3","message":{"id":"m3","role":"assistant","model":"claude-opus-4-1","content":[]}}"#,
+            ),
+        ];
+        let kinds: Vec<_> = model_breakdown(&messages)
+            .switches
+            .iter()
+            .map(|s| s.kind)
+            .collect();
+        assert_eq!(kinds, [SwitchKind::Switch, SwitchKind::Fallback]);
+    }
+}
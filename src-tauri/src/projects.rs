@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::types::Session;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    pub session_count: usize,
+    pub last_activity: f64,
+}
+
+/// Stable identifier for a project path (64-bit FNV-1a, hex encoded), so
+/// projects can be referenced without exposing or re-encoding the path.
+pub fn project_id(path: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in path.trim_end_matches('/').bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Claude Code's directory name for a project under `~/.claude/projects`.
+/// Every character other than an ASCII letter or digit becomes `-`, so
+/// different paths can share a directory.
+pub fn encode_project_path(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split(['/', '\\']).filter(|s| !s.is_empty()).collect()
+}
+
+/// Names each path by its shortest trailing run of segments that no other
+/// path shares, e.g. `acme/api` and `globex/api` instead of `api` twice.
+pub fn display_names<'a, I>(paths: I) -> HashMap<String, String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut unique: Vec<&str> = paths.into_iter().collect();
+    unique.sort_unstable();
+    unique.dedup();
+
+    let split: Vec<Vec<&str>> = unique.iter().map(|p| segments(p)).collect();
+    let suffix = |parts: &[&'a str], depth: usize| -> String {
+        parts[parts.len().saturating_sub(depth)..].join("/")
+    };
+
+    let mut names = HashMap::new();
+    for (i, path) in unique.iter().enumerate() {
+        let parts = &split[i];
+        if parts.is_empty() {
+            names.insert(path.to_string(), path.to_string());
+            continue;
+        }
+
+        let mut depth = 1;
+        while depth < parts.len() {
+            let candidate = suffix(parts, depth);
+            let clash = split
+                .iter()
+                .enumerate()
+                .any(|(j, other)| j != i && suffix(other, depth) == candidate);
+            if !clash {
+                break;
+            }
+            depth += 1;
+        }
+        names.insert(path.to_string(), suffix(parts, depth));
+    }
+
+    names
+}
+
+/// Groups sessions into projects by their stable project id, most recently
+/// active first.
+pub fn build_projects(sessions: &[Session]) -> Vec<Project> {
+    let mut projects: HashMap<&str, Project> = HashMap::new();
+
+    for session in sessions {
+        let project = projects
+            .entry(session.project_id.as_str())
+            .or_insert_with(|| Project {
+                id: session.project_id.clone(),
+                path: session.project.clone(),
+                name: session.project_name.clone(),
+                session_count: 0,
+                last_activity: 0.0,
+            });
+        project.session_count += 1;
+        project.last_activity = project.last_activity.max(session.timestamp);
+    }
+
+    let mut result: Vec<Project> = projects.into_values().collect();
+    result.sort_by(|a, b| {
+        b.last_activity
+            .partial_cmp(&a.last_activity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    result
+}
@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::prompts::normalize_prompt;
+use crate::store::{load_json, save_json};
+use crate::tags::clean_tags;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedPrompt {
+    /// Normalized text, so saving a near-identical prompt updates this one.
+    pub key: String,
+    pub text: String,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    /// In milliseconds since the epoch.
+    pub saved_at: i64,
+}
+
+/// Favorite prompts, persisted as JSON in the app data directory.
+pub struct PromptLibrary {
+    path: PathBuf,
+    prompts: RwLock<Vec<SavedPrompt>>,
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+impl PromptLibrary {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let prompts = load_json(&path).await?;
+
+        Ok(PromptLibrary {
+            path,
+            prompts: RwLock::new(prompts),
+        })
+    }
+
+    pub async fn snapshot(&self) -> Vec<SavedPrompt> {
+        self.prompts.read().await.clone()
+    }
+
+    /// Every distinct tag on a saved prompt, sorted.
+    pub async fn all_tags(&self) -> Vec<String> {
+        let prompts = self.prompts.read().await;
+        let unique: BTreeSet<&String> = prompts.iter().flat_map(|p| &p.tags).collect();
+        unique.into_iter().cloned().collect()
+    }
+
+    /// Saves a prompt, replacing a saved prompt with the same normalized text.
+    pub async fn save(
+        &self,
+        text: &str,
+        title: Option<String>,
+        tags: Vec<String>,
+    ) -> Result<SavedPrompt> {
+        let key = normalize_prompt(text);
+        if key.is_empty() {
+            bail!("Prompt is empty");
+        }
+        let saved = SavedPrompt {
+            key,
+            text: text.trim().to_string(),
+            title: title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+            tags: clean_tags(tags),
+            saved_at: now_millis(),
+        };
+
+        let mut prompts = self.prompts.write().await;
+        match prompts.iter_mut().find(|p| p.key == saved.key) {
+            Some(existing) => *existing = saved.clone(),
+            None => prompts.push(saved.clone()),
+        }
+        self.persist(&prompts).await?;
+        Ok(saved)
+    }
+
+    /// Returns false if no prompt was saved under `key`.
+    pub async fn remove(&self, key: &str) -> Result<bool> {
+        let mut prompts = self.prompts.write().await;
+        let before = prompts.len();
+        prompts.retain(|p| p.key != key);
+        if prompts.len() == before {
+            return Ok(false);
+        }
+        self.persist(&prompts).await?;
+        Ok(true)
+    }
+
+    async fn persist(&self, prompts: &[SavedPrompt]) -> Result<()> {
+        save_json(&self.path, prompts).await
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::projects::project_id;
+use crate::prompt_library::PromptLibrary;
+use crate::stats::{classify_user_text, is_human_prompt, message_text, timestamp_millis};
+use crate::storage::Storage;
+use crate::types::{HistoryEntry, UserMessageKind};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PromptQuery {
+    /// Project path or project id.
+    pub project: Option<String>,
+    /// Only prompts used at least this many times.
+    pub min_uses: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// A prompt and its near-identical variants, with how often it was used.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptUsage {
+    /// Normalized text shared by all variants; also the id of a saved prompt.
+    pub key: String,
+    /// The most recent wording.
+    pub text: String,
+    pub uses: usize,
+    pub session_ids: Vec<String>,
+    pub projects: Vec<String>,
+    /// In milliseconds since the epoch.
+    pub first_used_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+    pub saved: bool,
+    pub tags: Vec<String>,
+}
+
+struct Occurrence {
+    text: String,
+    session_id: Option<String>,
+    project: String,
+    at: Option<i64>,
+}
+
+/// Lowercases and drops punctuation and repeated whitespace, so prompts
+/// that only differ in those are treated as the same prompt.
+pub fn normalize_prompt(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Slash commands are tracked on their own rather than as prompts, and
+/// injected content is classified as in session transcripts.
+fn is_history_prompt(entry: &HistoryEntry) -> bool {
+    let text = entry.display.trim();
+    !text.is_empty()
+        && !text.starts_with('/')
+        && classify_user_text(text) == UserMessageKind::HumanPrompt
+}
+
+fn matches_project(query: &PromptQuery, project: &str, project_id: &str) -> bool {
+    query
+        .project
+        .as_ref()
+        .is_none_or(|p| p == project || p == project_id)
+}
+
+fn rank(occurrences: &[Occurrence], library: &HashMap<String, Vec<String>>) -> Vec<PromptUsage> {
+    let mut by_key: HashMap<String, PromptUsage> = HashMap::new();
+    for occurrence in occurrences {
+        let key = normalize_prompt(&occurrence.text);
+        if key.is_empty() {
+            continue;
+        }
+        let usage = by_key.entry(key.clone()).or_insert_with(|| PromptUsage {
+            saved: library.contains_key(&key),
+            tags: library.get(&key).cloned().unwrap_or_default(),
+            key,
+            text: occurrence.text.trim().to_string(),
+            uses: 0,
+            session_ids: Vec::new(),
+            projects: Vec::new(),
+            first_used_at: None,
+            last_used_at: None,
+        });
+        usage.uses += 1;
+        if let Some(ref id) = occurrence.session_id {
+            if !usage.session_ids.contains(id) {
+                usage.session_ids.push(id.clone());
+            }
+        }
+        if !usage.projects.contains(&occurrence.project) {
+            usage.projects.push(occurrence.project.clone());
+        }
+        if let Some(at) = occurrence.at {
+            if usage.first_used_at.is_none_or(|first| at < first) {
+                usage.first_used_at = Some(at);
+            }
+            if usage.last_used_at.is_none_or(|last| at >= last) {
+                usage.last_used_at = Some(at);
+                usage.text = occurrence.text.trim().to_string();
+            }
+        }
+    }
+
+    let mut prompts: Vec<PromptUsage> = by_key.into_values().collect();
+    prompts.sort_by(|a, b| {
+        b.uses
+            .cmp(&a.uses)
+            .then_with(|| b.last_used_at.cmp(&a.last_used_at))
+    });
+    prompts
+}
+
+/// Prompts typed across all sessions, most reused first. Prompts in
+/// `history.jsonl` are only counted when their session transcript could
+/// not be read, so each prompt is counted once.
+pub async fn collect_prompts(
+    storage: &Storage,
+    library: &PromptLibrary,
+    query: &PromptQuery,
+) -> Result<Vec<PromptUsage>> {
+    let history = storage.get_history().await;
+
+    let mut occurrences = Vec::new();
+    let mut read_sessions = HashSet::new();
+    let mut seen_in_sessions = HashSet::new();
+    storage
+        .for_each_session(query.project.as_deref(), |session, records| {
+            // Sessions whose transcript is gone read as empty
+            if !records.is_empty() {
+                read_sessions.insert(session.id.clone());
+            }
+            for msg in records.iter().filter(|m| is_human_prompt(m)) {
+                let text = message_text(msg);
+                seen_in_sessions.insert((session.project.clone(), normalize_prompt(&text)));
+                occurrences.push(Occurrence {
+                    text,
+                    session_id: Some(session.id.clone()),
+                    project: session.project.clone(),
+                    at: msg.timestamp.as_deref().and_then(timestamp_millis),
+                });
+            }
+        })
+        .await?;
+
+    for entry in history.iter().filter(|e| is_history_prompt(e)) {
+        if !matches_project(query, &entry.project, &project_id(&entry.project)) {
+            continue;
+        }
+        let counted = match entry.session_id {
+            Some(ref id) => read_sessions.contains(id),
+            None => seen_in_sessions
+                .contains(&(entry.project.clone(), normalize_prompt(&entry.display))),
+        };
+        if counted {
+            continue;
+        }
+        occurrences.push(Occurrence {
+            text: entry.display.clone(),
+            session_id: entry.session_id.clone(),
+            project: entry.project.clone(),
+            at: Some(entry.timestamp as i64),
+        });
+    }
+
+    let saved: HashMap<String, Vec<String>> = library
+        .snapshot()
+        .await
+        .into_iter()
+        .map(|p| (p.key, p.tags))
+        .collect();
+    let mut prompts: Vec<PromptUsage> = rank(&occurrences, &saved)
+        .into_iter()
+        .filter(|p| query.min_uses.is_none_or(|min| p.uses >= min))
+        .collect();
+    if let Some(limit) = query.limit {
+        prompts.truncate(limit);
+    }
+    Ok(prompts)
+}
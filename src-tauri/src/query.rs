@@ -0,0 +1,205 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Session;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionFilter {
+    /// Project path or project id.
+    pub project: Option<String>,
+    /// Inclusive lower bound, in milliseconds since the epoch.
+    pub from: Option<f64>,
+    /// Inclusive upper bound, in milliseconds since the epoch.
+    pub to: Option<f64>,
+    pub text: Option<String>,
+    /// Case-insensitive substring of any model the session used.
+    pub model: Option<String>,
+    /// Only sessions whose model did (or did not) change mid-session.
+    pub model_switched: Option<bool>,
+    pub tag: Option<String>,
+    pub min_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionSort {
+    #[default]
+    Newest,
+    Oldest,
+    MostTokens,
+    Longest,
+    MostMessages,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionQuery {
+    pub filter: SessionFilter,
+    pub sort: SessionSort,
+    /// Opaque cursor returned as `nextCursor` by the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPage {
+    pub sessions: Vec<Session>,
+    pub next_cursor: Option<String>,
+    pub total: usize,
+}
+
+impl SessionFilter {
+    fn matches(&self, session: &Session, tags: &HashMap<String, Vec<String>>) -> bool {
+        if let Some(ref project) = self.project {
+            if &session.project != project && &session.project_id != project {
+                return false;
+            }
+        }
+        if self.from.is_some_and(|from| session.timestamp < from) {
+            return false;
+        }
+        if self.to.is_some_and(|to| session.timestamp > to) {
+            return false;
+        }
+        if let Some(ref text) = self.text {
+            let needle = text.to_lowercase();
+            let summary = session.summary.as_ref().map(|s| &s.text);
+            let found = [&session.display, &session.project_name, &session.project]
+                .into_iter()
+                .chain(summary)
+                .any(|field| field.to_lowercase().contains(&needle));
+            if !found {
+                return false;
+            }
+        }
+        if let Some(ref model) = self.model {
+            let needle = model.to_lowercase();
+            let found = session
+                .stats
+                .as_ref()
+                .is_some_and(|s| s.models.iter().any(|m| m.to_lowercase().contains(&needle)));
+            if !found {
+                return false;
+            }
+        }
+        if let Some(switched) = self.model_switched {
+            if session.stats.as_ref().is_some_and(|s| s.model_switches > 0) != switched {
+                return false;
+            }
+        }
+        if let Some(ref tag) = self.tag {
+            if !tags.get(&session.id).is_some_and(|t| t.contains(tag)) {
+                return false;
+            }
+        }
+        if let Some(min_tokens) = self.min_tokens {
+            if session.stats.as_ref().map_or(0, |s| s.total_tokens) < min_tokens {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl SessionQuery {
+    /// Whether filtering or sorting reads session stats or summaries.
+    pub fn needs_stats(&self) -> bool {
+        let filter = &self.filter;
+        filter.text.is_some()
+            || filter.model.is_some()
+            || filter.model_switched.is_some()
+            || filter.min_tokens.is_some()
+            || !matches!(self.sort, SessionSort::Newest | SessionSort::Oldest)
+    }
+}
+
+impl SessionSort {
+    fn key(self, session: &Session) -> f64 {
+        let stats = session.stats.as_ref();
+        match self {
+            SessionSort::Newest | SessionSort::Oldest => session.timestamp,
+            SessionSort::MostTokens => stats.map_or(0, |s| s.total_tokens) as f64,
+            SessionSort::Longest => stats.map_or(0, |s| s.duration_ms) as f64,
+            SessionSort::MostMessages => stats.map_or(0, |s| s.message_count) as f64,
+        }
+    }
+
+    /// Orders by sort key (descending unless oldest-first), then by id so
+    /// that every session has a unique position a cursor can resume from.
+    fn compare(self, a: (f64, &str), b: (f64, &str)) -> Ordering {
+        let by_key = a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal);
+        let by_key = if self == SessionSort::Oldest {
+            by_key
+        } else {
+            by_key.reverse()
+        };
+        by_key.then_with(|| a.1.cmp(b.1))
+    }
+}
+
+fn encode_cursor(key: f64, id: &str) -> String {
+    format!("{}|{}", key, id)
+}
+
+fn decode_cursor(cursor: &str) -> Option<(f64, &str)> {
+    let (key, id) = cursor.split_once('|')?;
+    Some((key.parse().ok()?, id))
+}
+
+/// Filters, sorts and pages sessions. Cursors encode the sort position of the
+/// last returned session, so paging stays stable when sessions are added.
+pub fn query_sessions(
+    sessions: Vec<Session>,
+    tags: &HashMap<String, Vec<String>>,
+    query: &SessionQuery,
+) -> SessionPage {
+    let sort = query.sort;
+    let mut matching: Vec<(f64, Session)> = sessions
+        .into_iter()
+        .filter(|s| query.filter.matches(s, tags))
+        .map(|s| (sort.key(&s), s))
+        .collect();
+    matching.sort_by(|a, b| sort.compare((a.0, &a.1.id), (b.0, &b.1.id)));
+
+    let total = matching.len();
+    let start = match query.cursor.as_deref().and_then(decode_cursor) {
+        Some(after) => matching
+            .iter()
+            .position(|(key, s)| sort.compare((*key, &s.id), after) == Ordering::Greater)
+            .unwrap_or(total),
+        None => 0,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let end = (start + limit).min(total);
+
+    let next_cursor = if end < total {
+        let (key, last) = &matching[end - 1];
+        Some(encode_cursor(*key, &last.id))
+    } else {
+        None
+    };
+
+    let sessions = matching
+        .drain(start..end)
+        .map(|(_, mut session)| {
+            session.tags = tags.get(&session.id).cloned().unwrap_or_default();
+            session
+        })
+        .collect();
+
+    SessionPage {
+        sessions,
+        next_cursor,
+        total,
+    }
+}
@@ -0,0 +1,302 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    /// The shell the default terminal for this platform opens.
+    pub fn platform_default() -> Self {
+        if cfg!(target_os = "windows") {
+            Shell::Powershell
+        } else if cfg!(target_os = "macos") {
+            Shell::Zsh
+        } else {
+            Shell::Bash
+        }
+    }
+
+    /// Whether `arg` means the same to this shell without quotes.
+    fn is_safe_unquoted(self, arg: &str) -> bool {
+        let safe = match self {
+            Shell::Bash => "-_./:@%+=,",
+            // A leading `=` expands to the path of a command
+            Shell::Zsh => "-_./:@%+,",
+            // A leading `%` expands to process ids in older versions
+            Shell::Fish => "-_./:@+=,",
+            // `,` builds an array and `@` splats; a leading `-` would be
+            // read as a parameter name
+            Shell::Powershell => "-_./:\\",
+        };
+        if arg.is_empty() || (self == Shell::Powershell && arg.starts_with('-')) {
+            return false;
+        }
+        arg.chars()
+            .all(|c| c.is_ascii_alphanumeric() || safe.contains(c))
+    }
+
+    pub fn quote(self, arg: &str) -> String {
+        if self.is_safe_unquoted(arg) {
+            return arg.to_string();
+        }
+
+        match self {
+            Shell::Bash | Shell::Zsh => format!("'{}'", arg.replace('\'', r"'\''")),
+            Shell::Fish => format!("'{}'", arg.replace('\\', r"\\").replace('\'', r"\'")),
+            // PowerShell also ends single-quoted strings at typographic quotes
+            Shell::Powershell => {
+                let mut quoted = String::with_capacity(arg.len() + 2);
+                quoted.push('\'');
+                for c in arg.chars() {
+                    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+                quoted.push('\'');
+                quoted
+            }
+        }
+    }
+}
+
+/// Builds the command that changes into the project directory and resumes
+/// the session, quoted for the given shell.
+pub fn resume_command(shell: Shell, project_path: &str, session_id: &str) -> String {
+    let path = shell.quote(project_path);
+    let id = shell.quote(session_id);
+    match shell {
+        Shell::Powershell => format!("Set-Location -LiteralPath {}; claude --resume {}", path, id),
+        _ => format!("cd {} && claude --resume {}", path, id),
+    }
+}
+
+/// Terminal emulator invocation. `{command}` in any argument is replaced by
+/// the resume command, and `{escaped_command}` by the same command escaped
+/// for embedding in a double-quoted string (e.g. inside AppleScript).
+///
+/// Only read from the settings file: the webview renders transcript content,
+/// so it must not be able to choose what gets executed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerminalConfig {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl TerminalConfig {
+    pub fn platform_default(shell: Shell) -> Self {
+        let (program, args): (&str, Vec<String>) = if cfg!(target_os = "macos") {
+            // Terminal runs the script in the user's login shell
+            (
+                "osascript",
+                vec![
+                    "-e".to_string(),
+                    "tell application \"Terminal\" to do script \"{escaped_command}\"".to_string(),
+                    "-e".to_string(),
+                    "tell application \"Terminal\" to activate".to_string(),
+                ],
+            )
+        } else if cfg!(target_os = "windows") {
+            (
+                "powershell.exe",
+                vec![
+                    "-NoExit".to_string(),
+                    "-Command".to_string(),
+                    "{command}".to_string(),
+                ],
+            )
+        } else {
+            let mut args = vec!["-e".to_string()];
+            args.extend(shell_invocation(shell));
+            ("x-terminal-emulator", args)
+        };
+
+        TerminalConfig {
+            program: program.to_string(),
+            args,
+        }
+    }
+}
+
+/// Runs `{command}` in `shell` and keeps the shell open afterwards.
+fn shell_invocation(shell: Shell) -> Vec<String> {
+    let args: &[&str] = match shell {
+        Shell::Bash => &["bash", "-lc", "{command}; exec bash"],
+        Shell::Zsh => &["zsh", "-lc", "{command}; exec zsh"],
+        Shell::Fish => &["fish", "-l", "-c", "{command}; exec fish"],
+        Shell::Powershell => &["pwsh", "-NoExit", "-Command", "{command}"],
+    };
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// The terminal configured in the app data directory, if any.
+pub struct TerminalSettings {
+    configured: Option<TerminalConfig>,
+}
+
+impl TerminalSettings {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let configured = match fs::read_to_string(&path).await {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    log::error!(
+                        "Ignoring malformed terminal settings {}: {}",
+                        path.display(),
+                        e
+                    );
+                    None
+                }
+            },
+            Err(_) => None, // Use the platform default
+        };
+        Ok(TerminalSettings { configured })
+    }
+
+    pub fn terminal_for(&self, shell: Shell) -> TerminalConfig {
+        self.configured
+            .clone()
+            .unwrap_or_else(|| TerminalConfig::platform_default(shell))
+    }
+}
+
+/// Replaces the placeholders in a single pass, so a command that itself
+/// contains a placeholder is not expanded again.
+fn expand_placeholders(arg: &str, command: &str, escaped: &str) -> String {
+    const COMMAND: &str = "{command}";
+    const ESCAPED_COMMAND: &str = "{escaped_command}";
+
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(after) = tail.strip_prefix(COMMAND) {
+            out.push_str(command);
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix(ESCAPED_COMMAND) {
+            out.push_str(escaped);
+            rest = after;
+        } else {
+            out.push('{');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn open_in_terminal(terminal: &TerminalConfig, command: &str) -> Result<()> {
+    if terminal.program.trim().is_empty() {
+        bail!("No terminal program configured");
+    }
+
+    let escaped = command.replace('\\', r"\\").replace('"', "\\\"");
+    let args: Vec<String> = terminal
+        .args
+        .iter()
+        .map(|arg| expand_placeholders(arg, command, &escaped))
+        .collect();
+    let mut process = Command::new(&terminal.program);
+    process.args(&args);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+        process.creation_flags(CREATE_NEW_CONSOLE);
+    }
+
+    let mut child = process
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to launch {}: {}", terminal.program, e))?;
+    // Reap the launcher once it exits so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_placeholders_once() {
+        let command = "echo {escaped_command}";
+        assert_eq!(
+            expand_placeholders("run {command}; {x}", command, "escaped"),
+            "run echo {escaped_command}; {x}"
+        );
+        assert_eq!(
+            expand_placeholders("\"{escaped_command}\"", "a \"b\"", "a \\\"b\\\""),
+            "\"a \\\"b\\\"\""
+        );
+    }
+
+    #[test]
+    fn quotes_for_each_shell() {
+        let cases = [
+            (Shell::Bash, "a b", "'a b'"),
+            (Shell::Bash, "it's", r"'it'\''s'"),
+            (Shell::Bash, "a,b", "a,b"),
+            (Shell::Bash, r"C:\x", r"'C:\x'"),
+            (Shell::Zsh, "a b", "'a b'"),
+            (Shell::Zsh, "it's", r"'it'\''s'"),
+            (Shell::Zsh, "a,b", "a,b"),
+            (Shell::Zsh, r"a\b", r"'a\b'"),
+            (Shell::Zsh, "=ls", "'=ls'"),
+            (Shell::Fish, "a b", "'a b'"),
+            (Shell::Fish, "it's", r"'it\'s'"),
+            (Shell::Fish, "a,b", "a,b"),
+            (Shell::Fish, r"a\b", r"'a\\b'"),
+            (Shell::Powershell, "a b", "'a b'"),
+            (Shell::Powershell, "it's", "'it''s'"),
+            (Shell::Powershell, "it\u{2019}s", "'it\u{2019}\u{2019}s'"),
+            (Shell::Powershell, "a,b", "'a,b'"),
+            (Shell::Powershell, "@a", "'@a'"),
+            (Shell::Powershell, "-a", "'-a'"),
+            (Shell::Powershell, r"C:\Users\a", r"C:\Users\a"),
+            (Shell::Powershell, r"C:\my dir", r"'C:\my dir'"),
+        ];
+        for (shell, arg, quoted) in cases {
+            assert_eq!(shell.quote(arg), quoted, "{:?} {}", shell, arg);
+        }
+        assert_eq!(Shell::Bash.quote(""), "''");
+    }
+
+    #[test]
+    fn resume_command_quotes_path_and_id() {
+        assert_eq!(
+            resume_command(Shell::Bash, "/home/u/it's here", "abc-123"),
+            r"cd '/home/u/it'\''s here' && claude --resume abc-123"
+        );
+        assert_eq!(
+            resume_command(Shell::Fish, r"/home/u/a\b", "abc"),
+            r"cd '/home/u/a\\b' && claude --resume abc"
+        );
+        assert_eq!(
+            resume_command(Shell::Powershell, "/home/u/a,b", "abc"),
+            "Set-Location -LiteralPath '/home/u/a,b'; claude --resume abc"
+        );
+        assert_eq!(
+            resume_command(Shell::Zsh, "/home/u/a,b c", "abc"),
+            "cd '/home/u/a,b c' && claude --resume abc"
+        );
+    }
+
+    #[test]
+    fn linux_default_uses_requested_shell() {
+        let args = shell_invocation(Shell::Fish);
+        assert_eq!(args[0], "fish");
+        assert!(args.iter().all(|a| !a.contains("bash")));
+    }
+}
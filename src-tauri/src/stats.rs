@@ -0,0 +1,370 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::DateTime;
+use serde_json::Value;
+
+use crate::models::model_breakdown;
+use crate::tools::{ToolInput, ToolOutput};
+use crate::types::{
+    ContentBlock, ContentValue, ConversationMessage, SessionStats, TokenTotals, UserMessageKind,
+};
+
+/// Tags Claude Code wraps injected user content in, by the kind of content.
+const USER_CONTENT_PREFIXES: &[(&str, UserMessageKind)] = &[
+    ("<command-name>", UserMessageKind::SlashCommand),
+    ("<command-message>", UserMessageKind::SlashCommand),
+    (
+        "<local-command-stdout>",
+        UserMessageKind::LocalCommandOutput,
+    ),
+    (
+        "<local-command-stderr>",
+        UserMessageKind::LocalCommandOutput,
+    ),
+    ("<bash-input>", UserMessageKind::LocalCommandOutput),
+    ("<bash-stdout>", UserMessageKind::LocalCommandOutput),
+    ("<bash-stderr>", UserMessageKind::LocalCommandOutput),
+    ("<user-prompt-submit-hook>", UserMessageKind::HookFeedback),
+    ("Stop hook feedback:", UserMessageKind::HookFeedback),
+    ("SubagentStop hook feedback:", UserMessageKind::HookFeedback),
+    ("<system-reminder>", UserMessageKind::Meta),
+    ("[Request interrupted by user", UserMessageKind::Meta),
+];
+
+/// Placeholder model Claude Code records on locally generated assistant messages.
+pub const SYNTHETIC_MODEL: &str = "<synthetic>";
+
+/// Parses a transcript timestamp (RFC 3339) into milliseconds since the epoch.
+pub fn timestamp_millis(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.timestamp_millis())
+}
+
+/// True for user-role messages that only carry tool results back to the model.
+pub fn is_tool_result_message(msg: &ConversationMessage) -> bool {
+    match msg.message.as_ref().map(|m| &m.content) {
+        Some(ContentValue::Blocks(blocks)) => {
+            !blocks.is_empty()
+                && blocks
+                    .iter()
+                    .all(|b| matches!(b, ContentBlock::ToolResult { .. }))
+        }
+        _ => false,
+    }
+}
+
+/// What a user-role message holds; `None` for other record types.
+pub fn classify_user_message(msg: &ConversationMessage) -> Option<UserMessageKind> {
+    if msg.msg_type != "user" {
+        return None;
+    }
+    if is_tool_result_message(msg) {
+        return Some(UserMessageKind::ToolResult);
+    }
+    if msg.is_meta == Some(true)
+        || msg.is_sidechain == Some(true)
+        || msg.is_compact_summary == Some(true)
+    {
+        return Some(UserMessageKind::Meta);
+    }
+    Some(classify_user_text(&message_text(msg)))
+}
+
+/// What the text of a user message holds, judged by the tags Claude Code
+/// wraps injected content in.
+pub fn classify_user_text(text: &str) -> UserMessageKind {
+    let text = text.trim_start();
+    USER_CONTENT_PREFIXES
+        .iter()
+        .find(|(prefix, _)| text.starts_with(prefix))
+        .map_or(UserMessageKind::HumanPrompt, |&(_, kind)| kind)
+}
+
+/// True for user messages the user typed themselves.
+pub fn is_human_prompt(msg: &ConversationMessage) -> bool {
+    classify_user_message(msg) == Some(UserMessageKind::HumanPrompt)
+}
+
+/// A `ToolUse` block paired with the `ToolResult` that answered it, if any.
+pub struct ToolCall<'a> {
+    pub id: Option<&'a str>,
+    pub name: &'a str,
+    pub input: Option<&'a Value>,
+    /// The assistant message the call was made in.
+    pub message: &'a ConversationMessage,
+    pub result: Option<ToolResultRef<'a>>,
+}
+
+pub struct ToolResultRef<'a> {
+    pub is_error: bool,
+    pub content: Option<&'a Value>,
+    /// The message's `toolUseResult`, when it answers only this call.
+    pub output: Option<&'a Value>,
+}
+
+impl ToolCall<'_> {
+    pub fn tool(&self) -> ToolInput {
+        ToolInput::parse(self.name, self.input)
+    }
+
+    pub fn output(&self) -> Option<ToolOutput> {
+        let output = self.result.as_ref()?.output?;
+        Some(ToolOutput::parse(self.name, output))
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.result.as_ref().is_some_and(|r| r.is_error)
+    }
+}
+
+/// The content blocks of a message; empty for plain string content.
+pub fn content_blocks(msg: &ConversationMessage) -> &[ContentBlock] {
+    match msg.message.as_ref().map(|m| &m.content) {
+        Some(ContentValue::Blocks(blocks)) => blocks,
+        _ => &[],
+    }
+}
+
+/// Every tool call in the conversation, in order, matched to its result.
+pub fn tool_calls(messages: &[ConversationMessage]) -> Vec<ToolCall<'_>> {
+    let mut results = HashMap::new();
+    for msg in messages.iter().filter(|m| m.msg_type == "user") {
+        let blocks = content_blocks(msg);
+        let single_result = blocks
+            .iter()
+            .filter(|b| matches!(b, ContentBlock::ToolResult { .. }))
+            .count()
+            == 1;
+        for block in blocks {
+            if let ContentBlock::ToolResult {
+                tool_use_id: Some(id),
+                content,
+                is_error,
+                ..
+            } = block
+            {
+                results.insert(
+                    id.as_str(),
+                    ToolResultRef {
+                        is_error: is_error.unwrap_or(false),
+                        content: content.as_ref(),
+                        output: msg.tool_use_result.as_ref().filter(|_| single_result),
+                    },
+                );
+            }
+        }
+    }
+
+    let mut calls = Vec::new();
+    for msg in messages.iter().filter(|m| m.msg_type == "assistant") {
+        for block in content_blocks(msg) {
+            if let ContentBlock::ToolUse { id, name, input } = block {
+                calls.push(ToolCall {
+                    id: id.as_deref(),
+                    name: name.as_deref().unwrap_or_default(),
+                    input: input.as_ref(),
+                    message: msg,
+                    result: id.as_deref().and_then(|id| results.remove(id)),
+                });
+            }
+        }
+    }
+    calls
+}
+
+/// Paths of the files changed by file-editing tool calls.
+pub fn edited_files(calls: &[ToolCall]) -> BTreeSet<String> {
+    calls
+        .iter()
+        .filter_map(|c| c.tool().edited_file().map(str::to_string))
+        .collect()
+}
+
+/// The text blocks of a message joined together, or its plain string content.
+pub fn message_text(msg: &ConversationMessage) -> String {
+    match msg.message.as_ref().map(|m| &m.content) {
+        Some(ContentValue::Text(text)) => text.clone(),
+        Some(ContentValue::Blocks(blocks)) => blocks
+            .iter()
+            .filter_map(|b| match b {
+                ContentBlock::Text { text } => text.as_deref(),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => String::new(),
+    }
+}
+
+/// Claude Code writes one transcript line per content block, repeating the
+/// API message id and usage on each, so assistant lines are grouped by id.
+pub fn assistant_message_key(msg: &ConversationMessage) -> Option<&str> {
+    msg.message
+        .as_ref()
+        .and_then(|m| m.id.as_deref())
+        .or(msg.uuid.as_deref())
+}
+
+/// Leaves out the repeated lines of multi-block assistant responses, so
+/// each response's model and usage are seen once. Other records pass through.
+pub fn unique_responses<'a, I>(records: I) -> impl Iterator<Item = &'a ConversationMessage>
+where
+    I: IntoIterator<Item = &'a ConversationMessage>,
+{
+    let mut seen = HashSet::new();
+    records.into_iter().filter(move |msg| {
+        msg.msg_type != "assistant" || assistant_message_key(msg).is_none_or(|key| seen.insert(key))
+    })
+}
+
+/// Sums token usage across assistant messages, counting each API response once.
+pub fn token_totals<'a, I>(messages: I) -> TokenTotals
+where
+    I: IntoIterator<Item = &'a ConversationMessage>,
+{
+    let mut totals = TokenTotals::default();
+
+    for msg in unique_responses(messages) {
+        if msg.msg_type != "assistant" {
+            continue;
+        }
+        if let Some(usage) = msg.message.as_ref().and_then(|m| m.usage.as_ref()) {
+            totals.add(usage);
+        }
+    }
+
+    totals
+}
+
+pub fn compute_session_stats(messages: &[ConversationMessage]) -> SessionStats {
+    let mut stats = SessionStats::default();
+    let mut assistant_ids = HashSet::new();
+    let mut first_ms: Option<i64> = None;
+    let mut last_ms: Option<i64> = None;
+
+    for msg in messages {
+        // The launch directory identifies the project; later records may
+        // follow Claude into subdirectories
+        if stats.cwd.is_none() && msg.cwd.is_some() {
+            stats.cwd = msg.cwd.clone();
+        }
+        if msg.git_branch.as_deref().is_some_and(|b| !b.is_empty()) {
+            stats.git_branch = msg.git_branch.clone();
+        }
+
+        match msg.msg_type.as_str() {
+            "user" => {
+                if is_human_prompt(msg) {
+                    stats.user_turns += 1;
+                }
+            }
+            "assistant" => {
+                let model = msg.message.as_ref().and_then(|m| m.model.as_deref());
+                if let Some(model) = model {
+                    if model != SYNTHETIC_MODEL && !stats.models.iter().any(|m| m == model) {
+                        stats.models.push(model.to_string());
+                    }
+                }
+                match assistant_message_key(msg) {
+                    Some(key) => {
+                        assistant_ids.insert(key);
+                    }
+                    None => stats.assistant_turns += 1,
+                }
+            }
+            _ => continue,
+        }
+
+        stats.message_count += 1;
+
+        if let Some(ref ts) = msg.timestamp {
+            if let Some(ms) = timestamp_millis(ts) {
+                if first_ms.is_none_or(|f| ms < f) {
+                    first_ms = Some(ms);
+                    stats.first_message_at = Some(ts.clone());
+                }
+                if last_ms.is_none_or(|l| ms > l) {
+                    last_ms = Some(ms);
+                    stats.last_message_at = Some(ts.clone());
+                }
+            }
+        }
+    }
+
+    stats.assistant_turns += assistant_ids.len();
+    if let (Some(first), Some(last)) = (first_ms, last_ms) {
+        stats.duration_ms = (last - first).max(0) as u64;
+    }
+    stats.model_switches = model_breakdown(messages).switches.len();
+    stats.tokens = token_totals(messages);
+    stats.total_tokens = stats.tokens.total();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_user_text_by_prefix() {
+        let cases = [
+            ("fix the failing test", UserMessageKind::HumanPrompt),
+            (
+                "please read <command-name> docs",
+                UserMessageKind::HumanPrompt,
+            ),
+            (
+                "<command-name>/review</command-name>\n<command-args>src</command-args>",
+                UserMessageKind::SlashCommand,
+            ),
+            (
+                "<command-message>review is running…</command-message>",
+                UserMessageKind::SlashCommand,
+            ),
+            (
+                "<local-command-stdout>Total cost: $0.12</local-command-stdout>",
+                UserMessageKind::LocalCommandOutput,
+            ),
+            (
+                "<local-command-stderr>error</local-command-stderr>",
+                UserMessageKind::LocalCommandOutput,
+            ),
+            (
+                "<bash-input>ls</bash-input>",
+                UserMessageKind::LocalCommandOutput,
+            ),
+            (
+                "<bash-stdout>README.md</bash-stdout><bash-stderr></bash-stderr>",
+                UserMessageKind::LocalCommandOutput,
+            ),
+            (
+                "<bash-stderr>ls: denied</bash-stderr>",
+                UserMessageKind::LocalCommandOutput,
+            ),
+            (
+                "<user-prompt-submit-hook>context</user-prompt-submit-hook>",
+                UserMessageKind::HookFeedback,
+            ),
+            (
+                "Stop hook feedback:\n[./verify.sh]: failing",
+                UserMessageKind::HookFeedback,
+            ),
+            (
+                "SubagentStop hook feedback:\nmore",
+                UserMessageKind::HookFeedback,
+            ),
+            (
+                "  \n<system-reminder>todo list changed</system-reminder>",
+                UserMessageKind::Meta,
+            ),
+            (
+                "[Request interrupted by user for tool use]",
+                UserMessageKind::Meta,
+            ),
+        ];
+        for (text, kind) in cases {
+            assert_eq!(classify_user_text(text), kind, "{:?}", text);
+        }
+    }
+}
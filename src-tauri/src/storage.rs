@@ -1,19 +1,61 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use anyhow::Result;
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::chains::{build_chains, merge_conversations, own_records, session_links, SessionLinks};
+use crate::line_index::{read_record_at, truncate_tool_results, LineIndex};
+use crate::projects::{build_projects, display_names, encode_project_path, project_id, Project};
+use crate::stats::compute_session_stats;
+use crate::summary::summarize_session;
+use crate::types::{
+    ContentBlock, ContentValue, ConversationMessage, ConversationWindow, HistoryEntry, Session,
+    SessionStats, SessionSummary, StreamResult,
+};
+
+/// Records read from the start of a session file when looking for its cwd.
+const CWD_SCAN_LINES: usize = 20;
+
+/// Per-session metadata derived from the session file, valid while the
+/// file's modification time is unchanged.
+#[derive(Clone)]
+struct CachedSessionMeta {
+    modified: SystemTime,
+    stats: SessionStats,
+    summary: Option<SessionSummary>,
+    links: SessionLinks,
+}
+
+/// A session file in the projects directory, whether or not
+/// `history.jsonl` lists it (subagent and headless sessions are not).
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub session_id: String,
+    pub project: String,
+    pub project_id: String,
+}
 
-use crate::types::{ConversationMessage, HistoryEntry, Session, StreamResult};
+impl Transcript {
+    /// Whether the transcript belongs to `project` (path or id).
+    pub fn in_project(&self, project: &str) -> bool {
+        self.project == project || self.project_id == project
+    }
+}
 
 pub struct Storage {
     claude_dir: PathBuf,
     projects_dir: PathBuf,
     file_index: RwLock<HashMap<String, PathBuf>>,
     history_cache: RwLock<Option<Vec<HistoryEntry>>>,
+    meta_cache: RwLock<HashMap<String, CachedSessionMeta>>,
+    /// Locked per session, so indexing a large file does not block others.
+    line_indexes: RwLock<HashMap<String, Arc<Mutex<LineIndex>>>>,
 }
 
 impl Storage {
@@ -31,6 +73,8 @@ impl Storage {
             projects_dir,
             file_index: RwLock::new(HashMap::new()),
             history_cache: RwLock::new(None),
+            meta_cache: RwLock::new(HashMap::new()),
+            line_indexes: RwLock::new(HashMap::new()),
         })
     }
 
@@ -129,27 +173,11 @@ impl Storage {
         cache.as_ref().cloned().unwrap_or_default()
     }
 
-    fn encode_project_path(path: &str) -> String {
-        path.chars()
-            .map(|c| if c == '/' || c == '.' { '-' } else { c })
-            .collect()
-    }
-
-    fn get_project_name(project_path: &str) -> String {
-        project_path
-            .split('/')
-            .filter(|s| !s.is_empty())
-            .last()
-            .unwrap_or(project_path)
-            .to_string()
-    }
-
-    async fn find_session_by_timestamp(
-        &self,
-        encoded_project: &str,
-        timestamp: f64,
-    ) -> Option<String> {
-        let project_path = self.projects_dir.join(encoded_project);
+    /// Finds the session in a project's directory whose file was modified
+    /// closest to `timestamp`. Projects whose paths encode to the same
+    /// directory are told apart by the `cwd` recorded in each session.
+    async fn find_session_by_timestamp(&self, project: &str, timestamp: f64) -> Option<String> {
+        let project_path = self.projects_dir.join(encode_project_path(project));
         let mut files = fs::read_dir(&project_path).await.ok()?;
 
         let mut closest_file: Option<String> = None;
@@ -162,6 +190,12 @@ impl Storage {
                 continue;
             }
 
+            let session_id = file_name_str.trim_end_matches(".jsonl");
+            let cwd = self.read_session_cwd(session_id).await;
+            if cwd.is_some_and(|cwd| cwd != project) {
+                continue;
+            }
+
             if let Ok(meta) = entry.metadata().await {
                 if let Ok(mtime) = meta.modified() {
                     let mtime_ms = mtime
@@ -171,8 +205,7 @@ impl Storage {
                     let diff = (mtime_ms - timestamp).abs();
                     if diff < closest_diff {
                         closest_diff = diff;
-                        closest_file =
-                            Some(file_name_str.trim_end_matches(".jsonl").to_string());
+                        closest_file = Some(session_id.to_string());
                     }
                 }
             }
@@ -219,17 +252,96 @@ impl Storage {
         None
     }
 
+    /// Every prompt recorded in `history.jsonl`, oldest first.
+    pub async fn get_history(&self) -> Vec<HistoryEntry> {
+        self.ensure_history_cache().await
+    }
+
+    /// Every indexed session file, sorted by session id. The project is the
+    /// cwd recorded in the file, or the encoded directory name without one.
+    pub async fn get_transcripts(&self) -> Vec<Transcript> {
+        let mut files: Vec<(String, PathBuf)> = {
+            let index = self.file_index.read().await;
+            index
+                .iter()
+                .map(|(id, p)| (id.clone(), p.clone()))
+                .collect()
+        };
+        files.sort();
+
+        let mut transcripts = Vec::with_capacity(files.len());
+        for (session_id, path) in files {
+            let project = match self.read_session_cwd(&session_id).await {
+                Some(cwd) => cwd,
+                None => path
+                    .parent()
+                    .and_then(Path::file_name)
+                    .map(|dir| dir.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            };
+            transcripts.push(Transcript {
+                session_id,
+                project_id: project_id(&project),
+                project,
+            });
+        }
+        transcripts
+    }
+
+    /// Calls `f` with the records of every indexed session file `include`
+    /// accepts, leaving out records copied from a resumed session. Files
+    /// that cannot be read are logged and skipped.
+    pub async fn for_each_transcript(
+        &self,
+        include: impl Fn(&Transcript) -> bool,
+        mut f: impl FnMut(&Transcript, &[ConversationMessage]),
+    ) {
+        for transcript in self.get_transcripts().await {
+            if !include(&transcript) {
+                continue;
+            }
+            match self.read_own_session_records(&transcript.session_id).await {
+                Ok(records) => f(&transcript, &records),
+                Err(e) => log::error!("Error reading session {}: {}", transcript.session_id, e),
+            }
+        }
+    }
+
+    /// Calls `f` with the records of every session in `history.jsonl`,
+    /// optionally limited to a project (path or id), leaving out records
+    /// copied from a resumed session. Files that cannot be read are logged
+    /// and skipped.
+    pub async fn for_each_session(
+        &self,
+        project: Option<&str>,
+        mut f: impl FnMut(&Session, &[ConversationMessage]),
+    ) -> Result<()> {
+        for session in self.get_sessions().await? {
+            if project.is_some_and(|p| p != session.project && p != session.project_id) {
+                continue;
+            }
+            match self.read_own_session_records(&session.id).await {
+                Ok(records) => f(&session, &records),
+                Err(e) => log::error!("Error reading session {}: {}", session.id, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the sessions in `history.jsonl`, newest first. Stats, summaries
+    /// and chains are only filled in for sessions whose metadata is already
+    /// cached; `warm_session_meta` computes the rest in the background.
     pub async fn get_sessions(&self) -> Result<Vec<Session>> {
         let entries = self.ensure_history_cache().await;
         let mut sessions = Vec::new();
         let mut seen_ids = HashSet::new();
+        let mut links = HashMap::new();
 
         for entry in &entries {
             let session_id = if let Some(ref id) = entry.session_id {
                 Some(id.clone())
             } else {
-                let encoded = Self::encode_project_path(&entry.project);
-                self.find_session_by_timestamp(&encoded, entry.timestamp)
+                self.find_session_by_timestamp(&entry.project, entry.timestamp)
                     .await
             };
 
@@ -243,26 +355,77 @@ impl Storage {
             }
 
             seen_ids.insert(session_id.clone());
+            let meta = self.cached_session_meta(&session_id).await;
+            let (stats, summary) = match meta {
+                Some(m) => {
+                    links.insert(session_id.clone(), m.links);
+                    (Some(m.stats), m.summary)
+                }
+                None => (None, None),
+            };
+            // The cwd recorded in the session file is the exact project path
+            let cwd = match stats.as_ref() {
+                Some(stats) => stats.cwd.clone(),
+                None => self.read_session_cwd(&session_id).await,
+            };
+            let project = cwd.unwrap_or_else(|| entry.project.clone());
             sessions.push(Session {
                 id: session_id,
                 display: entry.display.clone(),
                 timestamp: entry.timestamp,
-                project: entry.project.clone(),
-                project_name: Self::get_project_name(&entry.project),
+                project_id: project_id(&project),
+                project,
+                project_name: String::new(),
+                stats,
+                summary,
+                tags: Vec::new(),
+                chain: None,
             });
         }
 
+        // Earlier sessions of a chain may be missing from history.jsonl
+        let mut pending: Vec<String> = links.values().filter_map(|l| l.previous.clone()).collect();
+        while let Some(id) = pending.pop() {
+            if links.contains_key(&id) {
+                continue;
+            }
+            if let Some(meta) = self.cached_session_meta(&id).await {
+                pending.extend(meta.links.previous.clone());
+                links.insert(id, meta.links);
+            }
+        }
+        let mut chains = build_chains(&links);
+        for session in &mut sessions {
+            session.chain = chains.remove(&session.id);
+        }
+
+        let names = display_names(sessions.iter().map(|s| s.project.as_str()));
+        for session in &mut sessions {
+            session.project_name = names.get(&session.project).cloned().unwrap_or_default();
+        }
+
         sessions.sort_by(|a, b| b.timestamp.partial_cmp(&a.timestamp).unwrap_or(std::cmp::Ordering::Equal));
         Ok(sessions)
     }
 
+    /// Computes the stats and summary of sessions listed before their
+    /// metadata was cached.
+    pub async fn fill_session_stats(&self, sessions: &mut [Session]) {
+        for session in sessions.iter_mut().filter(|s| s.stats.is_none()) {
+            if let Some(meta) = self.get_session_file_meta(&session.id).await {
+                session.stats = Some(meta.stats);
+                session.summary = meta.summary;
+            }
+        }
+    }
+
     pub async fn get_projects(&self) -> Result<Vec<String>> {
-        let entries = self.ensure_history_cache().await;
+        let sessions = self.get_sessions().await?;
         let mut projects = HashSet::new();
 
-        for entry in &entries {
-            if !entry.project.is_empty() {
-                projects.insert(entry.project.clone());
+        for session in &sessions {
+            if !session.project.is_empty() {
+                projects.insert(session.project.clone());
             }
         }
 
@@ -271,11 +434,210 @@ impl Storage {
         Ok(result)
     }
 
+    pub async fn get_project_list(&self) -> Result<Vec<Project>> {
+        let sessions = self.get_sessions().await?;
+        Ok(build_projects(&sessions))
+    }
+
+    /// The directory a session was started in, preferring the path recorded
+    /// in the session file over the one in `history.jsonl`.
+    pub async fn get_session_project_path(&self, session_id: &str) -> Result<Option<String>> {
+        if let Some(cwd) = self.get_session_stats(session_id).await.and_then(|s| s.cwd) {
+            return Ok(Some(cwd));
+        }
+        Ok(self.get_session_meta(session_id).await?.map(|s| s.project))
+    }
+
     pub async fn get_session_meta(&self, session_id: &str) -> Result<Option<Session>> {
         let sessions = self.get_sessions().await?;
         Ok(sessions.into_iter().find(|s| s.id == session_id))
     }
 
+    /// Reads every parseable record of a session file in file order,
+    /// including summary and system records.
+    pub async fn read_session_records(&self, session_id: &str) -> Result<Vec<ConversationMessage>> {
+        let file_path = match self.find_session_file(session_id).await {
+            Some(p) => p,
+            None => return Ok(Vec::new()),
+        };
+
+        let content = fs::read_to_string(&file_path).await?;
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| ConversationMessage::from_line(line).ok())
+            .collect())
+    }
+
+    /// Reads a session file only as far as the first record that carries
+    /// the directory the session was started in.
+    async fn read_session_cwd(&self, session_id: &str) -> Option<String> {
+        #[derive(serde::Deserialize)]
+        struct CwdRecord {
+            cwd: Option<String>,
+        }
+
+        let file_path = self.find_session_file(session_id).await?;
+        let file = fs::File::open(&file_path).await.ok()?;
+        let mut lines = BufReader::new(file).lines();
+        for _ in 0..CWD_SCAN_LINES {
+            let line = lines.next_line().await.ok()??;
+            if let Ok(CwdRecord { cwd: Some(cwd) }) = serde_json::from_str(&line) {
+                return Some(cwd);
+            }
+        }
+        None
+    }
+
+    /// Returns the cached metadata of a session file if the file has not
+    /// been modified since it was computed.
+    async fn cached_session_meta(&self, session_id: &str) -> Option<CachedSessionMeta> {
+        let file_path = self.find_session_file(session_id).await?;
+        let modified = fs::metadata(&file_path).await.ok()?.modified().ok()?;
+        let cache = self.meta_cache.read().await;
+        cache
+            .get(session_id)
+            .filter(|cached| cached.modified == modified)
+            .cloned()
+    }
+
+    /// Computes the metadata of every indexed session file that is not
+    /// cached yet, so that later `get_sessions` calls can include it.
+    pub async fn warm_session_meta(&self) {
+        let session_ids: Vec<String> = self.file_index.read().await.keys().cloned().collect();
+        for session_id in session_ids {
+            self.get_session_file_meta(&session_id).await;
+        }
+    }
+
+    /// Reads the records a session wrote itself, leaving out those copied in
+    /// from the session it resumed.
+    pub async fn read_own_session_records(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<ConversationMessage>> {
+        let records = self.read_session_records(session_id).await?;
+        Ok(own_records(session_id, records))
+    }
+
+    /// Returns metadata derived from a session file, recomputing it only
+    /// when the file has been modified since it was last cached.
+    async fn get_session_file_meta(&self, session_id: &str) -> Option<CachedSessionMeta> {
+        if let Some(cached) = self.cached_session_meta(session_id).await {
+            return Some(cached);
+        }
+        let file_path = self.find_session_file(session_id).await?;
+        let modified = fs::metadata(&file_path).await.ok()?.modified().ok()?;
+
+        let records = match self.read_session_records(session_id).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::error!("Error reading session {}: {}", session_id, e);
+                return None;
+            }
+        };
+        let mut links = session_links(session_id, &records);
+        if links.previous.is_none() && !links.external_refs.is_empty() {
+            links.previous = self
+                .find_uuid_owner(&file_path, session_id, &links.external_refs)
+                .await;
+        }
+        let records = own_records(session_id, records);
+        let meta = CachedSessionMeta {
+            modified,
+            stats: compute_session_stats(&records),
+            summary: summarize_session(&records),
+            links,
+        };
+
+        let mut cache = self.meta_cache.write().await;
+        cache.insert(session_id.to_string(), meta.clone());
+        Some(meta)
+    }
+
+    /// Finds the session in the same project directory that wrote one of
+    /// `uuids`. Records a session copied in from another are skipped, so
+    /// the match is the session the message was first written in.
+    async fn find_uuid_owner(
+        &self,
+        file_path: &Path,
+        session_id: &str,
+        uuids: &[String],
+    ) -> Option<String> {
+        #[derive(serde::Deserialize)]
+        struct OwnedRecord {
+            uuid: Option<String>,
+            #[serde(rename = "sessionId")]
+            session_id: Option<String>,
+        }
+
+        let needles: Vec<String> = uuids.iter().map(|u| format!("\"{}\"", u)).collect();
+        let mut files = fs::read_dir(file_path.parent()?).await.ok()?;
+        while let Ok(Some(entry)) = files.next_entry().await {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let owner = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(stem) if stem != session_id => stem.to_string(),
+                _ => continue,
+            };
+            let file = match fs::File::open(&path).await {
+                Ok(f) => f,
+                Err(_) => continue,
+            };
+            let mut lines = BufReader::new(file).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if !needles.iter().any(|n| line.contains(n.as_str())) {
+                    continue;
+                }
+                let record: OwnedRecord = match serde_json::from_str(&line) {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+                let written_here = record.session_id.as_deref().is_none_or(|id| id == owner);
+                if written_here && record.uuid.is_some_and(|u| uuids.contains(&u)) {
+                    return Some(owner);
+                }
+            }
+        }
+        None
+    }
+
+    pub async fn get_session_stats(&self, session_id: &str) -> Option<SessionStats> {
+        self.get_session_file_meta(session_id)
+            .await
+            .map(|m| m.stats)
+    }
+
+    pub async fn get_session_summary(&self, session_id: &str) -> Option<SessionSummary> {
+        self.get_session_file_meta(session_id)
+            .await
+            .and_then(|m| m.summary)
+    }
+
+    /// The conversation of every session in the chain, oldest first.
+    pub async fn get_chain_conversation(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<ConversationMessage>> {
+        let lineage = match self
+            .get_session_meta(session_id)
+            .await?
+            .and_then(|s| s.chain)
+        {
+            Some(chain) => chain.lineage,
+            None => vec![session_id.to_string()],
+        };
+
+        let mut conversations = Vec::new();
+        for id in &lineage {
+            conversations.push(self.get_conversation(id).await?);
+        }
+        Ok(merge_conversations(conversations))
+    }
+
     pub async fn get_conversation(&self, session_id: &str) -> Result<Vec<ConversationMessage>> {
         let file_path = match self.find_session_file(session_id).await {
             Some(p) => p,
@@ -296,7 +658,7 @@ impl Storage {
             if line.is_empty() {
                 continue;
             }
-            match serde_json::from_str::<ConversationMessage>(line) {
+            match ConversationMessage::from_line(line) {
                 Ok(msg) => {
                     if msg.msg_type == "user" || msg.msg_type == "assistant" {
                         messages.push(msg);
@@ -311,6 +673,116 @@ impl Storage {
         Ok(messages)
     }
 
+    /// Brings the session's line index up to date with the file on disk and
+    /// returns the offsets for `start..start + count` plus the total count.
+    async fn indexed_offsets(
+        &self,
+        file_path: &Path,
+        session_id: &str,
+        start: usize,
+        count: usize,
+    ) -> Result<(Vec<u64>, usize)> {
+        let file_size = fs::metadata(file_path).await?.len();
+        let entry = {
+            let mut indexes = self.line_indexes.write().await;
+            indexes.entry(session_id.to_string()).or_default().clone()
+        };
+        let mut index = entry.lock().await;
+
+        if file_size < index.scanned_to() {
+            // File was rewritten rather than appended to
+            *index = LineIndex::default();
+        }
+        if file_size > index.scanned_to() {
+            index.extend(file_path).await?;
+        }
+
+        let total = index.len();
+        let end = start.saturating_add(count).min(total);
+        let offsets = (start.min(end)..end)
+            .filter_map(|i| index.offset(i))
+            .collect();
+        Ok((offsets, total))
+    }
+
+    /// Returns `count` messages starting at `start`, in `get_conversation`
+    /// order, without reading the rest of the file. Tool results longer than
+    /// `max_tool_result_chars` are truncated.
+    pub async fn get_conversation_window(
+        &self,
+        session_id: &str,
+        start: usize,
+        count: usize,
+        max_tool_result_chars: Option<usize>,
+    ) -> Result<ConversationWindow> {
+        let file_path = match self.find_session_file(session_id).await {
+            Some(p) => p,
+            None => {
+                return Ok(ConversationWindow {
+                    messages: Vec::new(),
+                    start,
+                    total: 0,
+                })
+            }
+        };
+
+        let (offsets, total) = self
+            .indexed_offsets(&file_path, session_id, start, count)
+            .await?;
+
+        let mut messages = Vec::with_capacity(offsets.len());
+        for offset in offsets {
+            if let Some(mut msg) = read_record_at(&file_path, offset).await? {
+                if let Some(max_chars) = max_tool_result_chars {
+                    truncate_tool_results(&mut msg, max_chars);
+                }
+                messages.push(msg);
+            }
+        }
+
+        Ok(ConversationWindow {
+            messages,
+            start,
+            total,
+        })
+    }
+
+    /// Loads the untruncated content of a tool result in the message at
+    /// `message_index` of the conversation window.
+    pub async fn get_tool_result_content(
+        &self,
+        session_id: &str,
+        message_index: usize,
+        tool_use_id: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        let file_path = match self.find_session_file(session_id).await {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+
+        let (offsets, _) = self
+            .indexed_offsets(&file_path, session_id, message_index, 1)
+            .await?;
+        let msg = match offsets.first() {
+            Some(&offset) => read_record_at(&file_path, offset).await?,
+            None => None,
+        };
+
+        let blocks = match msg.and_then(|m| m.message).map(|m| m.content) {
+            Some(ContentValue::Blocks(blocks)) => blocks,
+            _ => return Ok(None),
+        };
+
+        Ok(blocks.into_iter().find_map(|block| match block {
+            ContentBlock::ToolResult {
+                tool_use_id: Some(id),
+                content,
+                ..
+            } if id == tool_use_id => content,
+            _ => None,
+        }))
+    }
+
     pub async fn get_conversation_stream(
         &self,
         session_id: &str,
@@ -372,7 +844,7 @@ impl Storage {
                 continue;
             }
 
-            match serde_json::from_str::<ConversationMessage>(trimmed) {
+            match ConversationMessage::from_line(trimmed) {
                 Ok(msg) => {
                     if msg.msg_type == "user" || msg.msg_type == "assistant" {
                         messages.push(msg);
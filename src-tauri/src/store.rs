@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::fs;
+
+/// Reads a JSON file kept in the app data directory. A missing file gives
+/// the default value; a malformed one is moved aside so the next write does
+/// not destroy it.
+pub async fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    let content = match fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(_) => return Ok(T::default()), // Nothing saved yet
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            let backup = sibling(path, &format!("{}.bak", now_millis()));
+            fs::rename(path, &backup).await?;
+            log::error!(
+                "Malformed {}, moved to {}: {}",
+                path.display(),
+                backup.display(),
+                e
+            );
+            Ok(T::default())
+        }
+    }
+}
+
+/// Writes a JSON file by replacing it with a fully written temporary file,
+/// so a crash mid-write never leaves it truncated.
+pub async fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let temp = sibling(path, "tmp");
+    fs::write(&temp, serde_json::to_string_pretty(value)?).await?;
+    fs::rename(&temp, path).await?;
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
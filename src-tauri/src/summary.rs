@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+use crate::stats::{edited_files, is_human_prompt, message_text, tool_calls};
+use crate::types::{ConversationMessage, SessionSummary, SummarySource};
+
+const PROMPT_CHARS: usize = 120;
+const OUTCOME_CHARS: usize = 160;
+const LISTED_FILES: usize = 3;
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// First prompt, last assistant reply and edited files, for sessions Claude
+/// Code never summarized.
+fn extractive_summary(records: &[ConversationMessage]) -> Option<String> {
+    let prompt = records
+        .iter()
+        .filter(|m| is_human_prompt(m))
+        .map(message_text)
+        .find(|t| !t.trim().is_empty());
+    let outcome = records
+        .iter()
+        .rev()
+        .filter(|m| m.msg_type == "assistant")
+        .map(message_text)
+        .find(|t| !t.trim().is_empty());
+    let files = edited_files(&tool_calls(records));
+
+    let mut parts = Vec::new();
+    if let Some(prompt) = prompt {
+        parts.push(shorten(&prompt, PROMPT_CHARS));
+    }
+    if let Some(outcome) = outcome {
+        let first_line = outcome.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        parts.push(shorten(first_line, OUTCOME_CHARS));
+    }
+    if !files.is_empty() {
+        let mut listed: Vec<&str> = files
+            .iter()
+            .take(LISTED_FILES)
+            .map(|f| file_name(f))
+            .collect();
+        let more = files.len().saturating_sub(LISTED_FILES);
+        let more = if more > 0 {
+            format!(" (+{} more)", more)
+        } else {
+            String::new()
+        };
+        listed.sort_unstable();
+        parts.push(format!("Edited {}{}", listed.join(", "), more));
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" — "))
+    }
+}
+
+/// The latest recorded summary of this session, falling back to an
+/// extractive one. Resumed sessions carry summaries of other conversations
+/// too, so only summaries whose leaf message is in this file count.
+pub fn summarize_session(records: &[ConversationMessage]) -> Option<SessionSummary> {
+    let uuids: HashSet<&str> = records.iter().filter_map(|m| m.uuid.as_deref()).collect();
+    let recorded = records
+        .iter()
+        .rev()
+        .filter(|m| m.msg_type == "summary")
+        .filter(|m| {
+            m.leaf_uuid
+                .as_deref()
+                .is_some_and(|leaf| uuids.contains(leaf))
+        })
+        .find_map(|m| m.summary.as_deref().filter(|s| !s.trim().is_empty()));
+
+    match recorded {
+        Some(text) => Some(SessionSummary {
+            text: text.to_string(),
+            source: SummarySource::Recorded,
+        }),
+        None => extractive_summary(records).map(|text| SessionSummary {
+            text,
+            source: SummarySource::Extractive,
+        }),
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use tokio::sync::RwLock;
+
+use crate::store::{load_json, save_json};
+
+/// Trims tags and drops empty and repeated ones, keeping the first
+/// occurrence's position.
+pub fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !cleaned.contains(&tag) {
+            cleaned.push(tag);
+        }
+    }
+    cleaned
+}
+
+/// User-assigned session tags, persisted as JSON in the app data directory.
+pub struct TagStore {
+    path: PathBuf,
+    tags: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl TagStore {
+    pub async fn load(path: PathBuf) -> Result<Self> {
+        let tags = load_json(&path).await?;
+
+        Ok(TagStore {
+            path,
+            tags: RwLock::new(tags),
+        })
+    }
+
+    pub async fn snapshot(&self) -> HashMap<String, Vec<String>> {
+        self.tags.read().await.clone()
+    }
+
+    pub async fn get(&self, session_id: &str) -> Vec<String> {
+        let tags = self.tags.read().await;
+        tags.get(session_id).cloned().unwrap_or_default()
+    }
+
+    /// Every distinct tag in use, sorted.
+    pub async fn all_tags(&self) -> Vec<String> {
+        let tags = self.tags.read().await;
+        let unique: BTreeSet<&String> = tags.values().flatten().collect();
+        unique.into_iter().cloned().collect()
+    }
+
+    pub async fn set(&self, session_id: &str, tags: Vec<String>) -> Result<Vec<String>> {
+        let cleaned = clean_tags(tags);
+        let mut all = self.tags.write().await;
+        if cleaned.is_empty() {
+            all.remove(session_id);
+        } else {
+            all.insert(session_id.to_string(), cleaned.clone());
+        }
+
+        save_json(&self.path, &*all).await?;
+        Ok(cleaned)
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::stats::{assistant_message_key, content_blocks};
+use crate::storage::Storage;
+use crate::tools::ToolInput;
+use crate::types::{ContentBlock, ContentValue, ConversationMessage};
+
+const ACTION_PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionKind {
+    Text,
+    ToolUse,
+}
+
+/// What Claude did right after thinking, within the same response.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowingAction {
+    pub kind: ActionKind,
+    pub tool: Option<String>,
+    pub target: Option<String>,
+    pub preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingBlock {
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub model: Option<String>,
+    pub text: String,
+    pub chars: usize,
+    pub words: usize,
+    pub following_action: Option<FollowingAction>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingVolume {
+    pub blocks: usize,
+    pub chars: usize,
+    pub words: usize,
+}
+
+impl ThinkingVolume {
+    fn add(&mut self, block: &ThinkingBlock) {
+        self.blocks += 1;
+        self.chars += block.chars;
+        self.words += block.words;
+    }
+
+    fn merge(&mut self, other: &ThinkingVolume) {
+        self.blocks += other.blocks;
+        self.chars += other.chars;
+        self.words += other.words;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionThinking {
+    pub blocks: Vec<ThinkingBlock>,
+    pub total: ThinkingVolume,
+    pub by_model: BTreeMap<String, ThinkingVolume>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionThinkingVolume {
+    pub session_id: String,
+    pub project: String,
+    #[serde(flatten)]
+    pub volume: ThinkingVolume,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingReport {
+    pub total: ThinkingVolume,
+    pub by_model: BTreeMap<String, ThinkingVolume>,
+    /// Sessions with any thinking, most thinking first.
+    pub sessions: Vec<SessionThinkingVolume>,
+}
+
+fn following_action(block: &ContentBlock) -> Option<FollowingAction> {
+    match block {
+        ContentBlock::Text { text } => Some(FollowingAction {
+            kind: ActionKind::Text,
+            tool: None,
+            target: None,
+            preview: text
+                .as_deref()
+                .map(|t| t.trim().chars().take(ACTION_PREVIEW_CHARS).collect()),
+        }),
+        ContentBlock::ToolUse { name, input, .. } => {
+            let name = name.as_deref().unwrap_or_default();
+            Some(FollowingAction {
+                kind: ActionKind::ToolUse,
+                tool: Some(name.to_string()),
+                target: ToolInput::parse(name, input.as_ref())
+                    .target()
+                    .map(str::to_string),
+                preview: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Every thinking block in the session, paired with the next text or tool
+/// call of the same response.
+pub fn thinking_blocks(records: &[ConversationMessage]) -> Vec<ThinkingBlock> {
+    // Each transcript line holds one block of a response, so flatten the
+    // blocks and look ahead within the same response
+    let blocks: Vec<(&ConversationMessage, &ContentBlock)> = records
+        .iter()
+        .filter(|m| m.msg_type == "assistant")
+        .flat_map(|m| content_blocks(m).iter().map(move |b| (m, b)))
+        .collect();
+
+    blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &(msg, block))| {
+            let text = match block {
+                ContentBlock::Thinking { thinking } => thinking.clone().unwrap_or_default(),
+                _ => return None,
+            };
+            let key = assistant_message_key(msg);
+            let following_action = blocks[i + 1..]
+                .iter()
+                .take_while(|(m, _)| assistant_message_key(m) == key)
+                .find_map(|(_, b)| following_action(b));
+
+            Some(ThinkingBlock {
+                uuid: msg.uuid.clone(),
+                timestamp: msg.timestamp.clone(),
+                model: msg.message.as_ref().and_then(|m| m.model.clone()),
+                chars: text.chars().count(),
+                words: text.split_whitespace().count(),
+                text,
+                following_action,
+            })
+        })
+        .collect()
+}
+
+fn volume_by_model(blocks: &[ThinkingBlock]) -> BTreeMap<String, ThinkingVolume> {
+    let mut by_model: BTreeMap<String, ThinkingVolume> = BTreeMap::new();
+    for block in blocks {
+        let model = block.model.clone().unwrap_or_else(|| "unknown".to_string());
+        by_model.entry(model).or_default().add(block);
+    }
+    by_model
+}
+
+pub fn session_thinking(records: &[ConversationMessage]) -> SessionThinking {
+    let blocks = thinking_blocks(records);
+    let mut total = ThinkingVolume::default();
+    for block in &blocks {
+        total.add(block);
+    }
+    SessionThinking {
+        by_model: volume_by_model(&blocks),
+        total,
+        blocks,
+    }
+}
+
+/// Thinking volume across all sessions, per model and per session.
+pub async fn collect_thinking_report(storage: &Storage) -> Result<ThinkingReport> {
+    let mut report = ThinkingReport::default();
+
+    storage
+        .for_each_session(None, |session, records| {
+            let thinking = session_thinking(records);
+            if thinking.total.blocks == 0 {
+                return;
+            }
+
+            for (model, volume) in &thinking.by_model {
+                report
+                    .by_model
+                    .entry(model.clone())
+                    .or_default()
+                    .merge(volume);
+            }
+            report.total.merge(&thinking.total);
+            report.sessions.push(SessionThinkingVolume {
+                session_id: session.id.clone(),
+                project: session.project.clone(),
+                volume: thinking.total,
+            });
+        })
+        .await?;
+
+    report
+        .sessions
+        .sort_by_key(|s| std::cmp::Reverse(s.volume.chars));
+    Ok(report)
+}
+
+/// Removes thinking blocks, dropping messages that held nothing else.
+pub fn strip_thinking(messages: &mut Vec<ConversationMessage>) {
+    messages.retain_mut(|msg| {
+        let blocks = match msg.message.as_mut().map(|m| &mut m.content) {
+            Some(ContentValue::Blocks(blocks)) => blocks,
+            _ => return true,
+        };
+        let had_blocks = !blocks.is_empty();
+        blocks.retain(|b| !matches!(b, ContentBlock::Thinking { .. }));
+        !had_blocks || !blocks.is_empty()
+    });
+}
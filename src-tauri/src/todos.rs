@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::stats::tool_calls;
+use crate::tools::{TodoItem, TodoStatus, ToolInput};
+use crate::types::ConversationMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TodoChangeKind {
+    Added,
+    StatusChanged,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoChange {
+    pub kind: TodoChangeKind,
+    pub content: String,
+    pub from: Option<TodoStatus>,
+    pub to: Option<TodoStatus>,
+    /// The assistant message whose `TodoWrite` call made the change.
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedTodo {
+    pub content: String,
+    pub active_form: Option<String>,
+    pub status: TodoStatus,
+    pub added_at: Option<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    /// Set when a later update dropped the item from the list.
+    pub removed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoProgress {
+    pub updates: usize,
+    /// Every item the list ever held, in the order first added.
+    pub items: Vec<TrackedTodo>,
+    pub timeline: Vec<TodoChange>,
+    pub pending: usize,
+    pub in_progress: usize,
+    pub completed: usize,
+}
+
+/// Items are matched across updates by id when Claude Code assigns one,
+/// otherwise by their text.
+fn item_key(item: &TodoItem) -> String {
+    match item.id {
+        Some(ref id) => format!("id:{}", id),
+        None => format!("content:{}", item.content),
+    }
+}
+
+/// Replays every successful `TodoWrite` call in the session to rebuild how
+/// the todo list evolved.
+pub fn todo_progress(records: &[ConversationMessage]) -> TodoProgress {
+    let mut progress = TodoProgress::default();
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+
+    for call in tool_calls(records) {
+        if call.is_error() {
+            continue;
+        }
+        let todos = match call.tool() {
+            ToolInput::TodoWrite(input) => input.todos,
+            _ => continue,
+        };
+        progress.updates += 1;
+
+        let uuid = call.message.uuid.clone();
+        let timestamp = call.message.timestamp.clone();
+        let change = |kind, content: &str, from, to| TodoChange {
+            kind,
+            content: content.to_string(),
+            from,
+            to,
+            uuid: uuid.clone(),
+            timestamp: timestamp.clone(),
+        };
+
+        let mut present = vec![false; progress.items.len()];
+        for todo in &todos {
+            let key = item_key(todo);
+            let index = match index_by_key.get(&key) {
+                Some(&i) if progress.items[i].removed_at.is_none() => i,
+                _ => {
+                    let at = |status| (todo.status == status).then(|| timestamp.clone()).flatten();
+                    progress.items.push(TrackedTodo {
+                        content: todo.content.clone(),
+                        active_form: todo.active_form.clone(),
+                        status: todo.status,
+                        added_at: timestamp.clone(),
+                        started_at: at(TodoStatus::InProgress),
+                        completed_at: at(TodoStatus::Completed),
+                        removed_at: None,
+                    });
+                    present.push(true);
+                    index_by_key.insert(key, progress.items.len() - 1);
+                    progress.timeline.push(change(
+                        TodoChangeKind::Added,
+                        &todo.content,
+                        None,
+                        Some(todo.status),
+                    ));
+                    continue;
+                }
+            };
+
+            present[index] = true;
+            let item = &mut progress.items[index];
+            item.content = todo.content.clone();
+            item.active_form = todo.active_form.clone();
+            if item.status == todo.status {
+                continue;
+            }
+            match todo.status {
+                TodoStatus::InProgress if item.started_at.is_none() => {
+                    item.started_at = timestamp.clone()
+                }
+                TodoStatus::Completed => item.completed_at = timestamp.clone(),
+                // Reopened
+                _ => item.completed_at = None,
+            }
+            let from = Some(item.status);
+            item.status = todo.status;
+            progress.timeline.push(change(
+                TodoChangeKind::StatusChanged,
+                &todo.content,
+                from,
+                Some(todo.status),
+            ));
+        }
+
+        for (index, item) in progress.items.iter_mut().enumerate() {
+            if present[index] || item.removed_at.is_some() {
+                continue;
+            }
+            item.removed_at = timestamp.clone();
+            progress.timeline.push(change(
+                TodoChangeKind::Removed,
+                &item.content,
+                Some(item.status),
+                None,
+            ));
+        }
+    }
+
+    for item in progress.items.iter().filter(|i| i.removed_at.is_none()) {
+        match item.status {
+            TodoStatus::Pending => progress.pending += 1,
+            TodoStatus::InProgress => progress.in_progress += 1,
+            TodoStatus::Completed => progress.completed += 1,
+        }
+    }
+    progress
+}
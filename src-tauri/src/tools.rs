@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::stats::tool_calls;
+use crate::types::ConversationMessage;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BashInput {
+    pub command: String,
+    pub description: Option<String>,
+    pub timeout: Option<u64>,
+    pub run_in_background: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadInput {
+    pub file_path: String,
+    pub offset: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteInput {
+    pub file_path: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditInput {
+    pub file_path: String,
+    #[serde(default)]
+    pub old_string: String,
+    #[serde(default)]
+    pub new_string: String,
+    pub replace_all: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditOperation {
+    #[serde(default)]
+    pub old_string: String,
+    #[serde(default)]
+    pub new_string: String,
+    pub replace_all: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiEditInput {
+    pub file_path: String,
+    #[serde(default)]
+    pub edits: Vec<EditOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobInput {
+    pub pattern: String,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepInput {
+    pub pattern: String,
+    pub path: Option<String>,
+    pub glob: Option<String>,
+    #[serde(rename = "type")]
+    pub file_type: Option<String>,
+    pub output_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskInput {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub prompt: String,
+    pub subagent_type: Option<String>,
+    pub model: Option<String>,
+    pub run_in_background: Option<bool>,
+    pub resume: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub content: String,
+    pub status: TodoStatus,
+    #[serde(rename = "activeForm")]
+    pub active_form: Option<String>,
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoWriteInput {
+    #[serde(default)]
+    pub todos: Vec<TodoItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebFetchInput {
+    pub url: String,
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSearchInput {
+    pub query: String,
+    pub allowed_domains: Option<Vec<String>>,
+    pub blocked_domains: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotebookEditInput {
+    pub notebook_path: String,
+    #[serde(default)]
+    pub new_source: String,
+    pub cell_id: Option<String>,
+    pub cell_type: Option<String>,
+    pub edit_mode: Option<String>,
+}
+
+/// The input of a `tool_use` block, typed for the built-in tools. Inputs of
+/// other tools, or ones that don't match the expected shape, are kept as is.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "tool")]
+pub enum ToolInput {
+    Bash(BashInput),
+    Read(ReadInput),
+    Write(WriteInput),
+    Edit(EditInput),
+    MultiEdit(MultiEditInput),
+    Glob(GlobInput),
+    Grep(GrepInput),
+    Task(TaskInput),
+    TodoWrite(TodoWriteInput),
+    WebFetch(WebFetchInput),
+    WebSearch(WebSearchInput),
+    NotebookEdit(NotebookEditInput),
+    Unknown { name: String, input: Value },
+}
+
+fn typed<T, U>(value: &Value, wrap: impl FnOnce(T) -> U) -> Option<U>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    T::deserialize(value).ok().map(wrap)
+}
+
+impl ToolInput {
+    pub fn parse(name: &str, input: Option<&Value>) -> Self {
+        let value = input.unwrap_or(&Value::Null);
+        let parsed = match name {
+            "Bash" => typed(value, ToolInput::Bash),
+            "Read" => typed(value, ToolInput::Read),
+            "Write" => typed(value, ToolInput::Write),
+            "Edit" => typed(value, ToolInput::Edit),
+            "MultiEdit" => typed(value, ToolInput::MultiEdit),
+            "Glob" => typed(value, ToolInput::Glob),
+            "Grep" => typed(value, ToolInput::Grep),
+            "Task" => typed(value, ToolInput::Task),
+            "TodoWrite" => typed(value, ToolInput::TodoWrite),
+            "WebFetch" => typed(value, ToolInput::WebFetch),
+            "WebSearch" => typed(value, ToolInput::WebSearch),
+            "NotebookEdit" => typed(value, ToolInput::NotebookEdit),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| ToolInput::Unknown {
+            name: name.to_string(),
+            input: value.clone(),
+        })
+    }
+
+    /// The argument that best identifies what the call acted on.
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            ToolInput::Bash(i) => Some(&i.command),
+            ToolInput::Read(i) => Some(&i.file_path),
+            ToolInput::Write(i) => Some(&i.file_path),
+            ToolInput::Edit(i) => Some(&i.file_path),
+            ToolInput::MultiEdit(i) => Some(&i.file_path),
+            ToolInput::NotebookEdit(i) => Some(&i.notebook_path),
+            ToolInput::Glob(i) => Some(&i.pattern),
+            ToolInput::Grep(i) => Some(&i.pattern),
+            ToolInput::WebFetch(i) => Some(&i.url),
+            ToolInput::WebSearch(i) => Some(&i.query),
+            ToolInput::Task(i) => Some(&i.description),
+            ToolInput::TodoWrite(_) | ToolInput::Unknown { .. } => None,
+        }
+    }
+
+    /// The file the call changes, for file-editing tools.
+    pub fn edited_file(&self) -> Option<&str> {
+        match self {
+            ToolInput::Write(i) => Some(&i.file_path),
+            ToolInput::Edit(i) => Some(&i.file_path),
+            ToolInput::MultiEdit(i) => Some(&i.file_path),
+            ToolInput::NotebookEdit(i) => Some(&i.notebook_path),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BashOutput {
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+    #[serde(default)]
+    pub interrupted: bool,
+    pub return_code_interpretation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadFile {
+    pub file_path: String,
+    pub num_lines: Option<u64>,
+    pub start_line: Option<u64>,
+    pub total_lines: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadOutput {
+    pub file: ReadFile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteOutput {
+    /// `create` or `update`.
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub file_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditOutput {
+    pub file_path: String,
+    pub user_modified: Option<bool>,
+    pub replace_all: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobOutput {
+    #[serde(default)]
+    pub filenames: Vec<String>,
+    pub num_files: Option<u64>,
+    pub truncated: Option<bool>,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrepOutput {
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub filenames: Vec<String>,
+    pub num_files: Option<u64>,
+    pub num_lines: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskOutput {
+    pub total_duration_ms: Option<u64>,
+    pub total_tokens: Option<u64>,
+    pub total_tool_use_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoWriteOutput {
+    #[serde(default)]
+    pub old_todos: Vec<TodoItem>,
+    #[serde(default)]
+    pub new_todos: Vec<TodoItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebFetchOutput {
+    pub url: Option<String>,
+    pub code: Option<u16>,
+    pub code_text: Option<String>,
+    pub bytes: Option<u64>,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSearchOutput {
+    pub query: Option<String>,
+    pub duration_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotebookEditOutput {
+    pub cell_id: Option<String>,
+    pub cell_type: Option<String>,
+    pub edit_mode: Option<String>,
+    pub error: Option<String>,
+}
+
+/// The `toolUseResult` Claude Code records next to a tool result, typed for
+/// the built-in tools. Failed calls usually record only an error string.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "tool", content = "output")]
+pub enum ToolOutput {
+    Bash(BashOutput),
+    Read(ReadOutput),
+    Write(WriteOutput),
+    Edit(EditOutput),
+    MultiEdit(EditOutput),
+    Glob(GlobOutput),
+    Grep(GrepOutput),
+    Task(TaskOutput),
+    TodoWrite(TodoWriteOutput),
+    WebFetch(WebFetchOutput),
+    WebSearch(WebSearchOutput),
+    NotebookEdit(NotebookEditOutput),
+    Text(String),
+    Unknown(Value),
+}
+
+impl ToolOutput {
+    pub fn parse(name: &str, output: &Value) -> Self {
+        if let Value::String(text) = output {
+            return ToolOutput::Text(text.clone());
+        }
+        let parsed = match name {
+            "Bash" => typed(output, ToolOutput::Bash),
+            "Read" => typed(output, ToolOutput::Read),
+            "Write" => typed(output, ToolOutput::Write),
+            "Edit" => typed(output, ToolOutput::Edit),
+            "MultiEdit" => typed(output, ToolOutput::MultiEdit),
+            "Glob" => typed(output, ToolOutput::Glob),
+            "Grep" => typed(output, ToolOutput::Grep),
+            "Task" => typed(output, ToolOutput::Task),
+            "TodoWrite" => typed(output, ToolOutput::TodoWrite),
+            "WebFetch" => typed(output, ToolOutput::WebFetch),
+            "WebSearch" => typed(output, ToolOutput::WebSearch),
+            "NotebookEdit" => typed(output, ToolOutput::NotebookEdit),
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| ToolOutput::Unknown(output.clone()))
+    }
+}
+
+/// Splits the name of a tool provided by an MCP server,
+/// `mcp__<server>__<tool>`, into the server and tool names.
+pub fn parse_mcp_tool_name(name: &str) -> Option<(&str, &str)> {
+    let (server, tool) = name.strip_prefix("mcp__")?.split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some((server, tool))
+}
+
+/// A tool call with its typed input and the outcome recorded for it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallRecord {
+    pub tool_use_id: Option<String>,
+    pub uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub input: ToolInput,
+    pub output: Option<ToolOutput>,
+    pub is_error: bool,
+}
+
+pub fn tool_call_records(messages: &[ConversationMessage]) -> Vec<ToolCallRecord> {
+    tool_calls(messages)
+        .iter()
+        .map(|call| ToolCallRecord {
+            tool_use_id: call.id.map(str::to_string),
+            uuid: call.message.uuid.clone(),
+            timestamp: call.message.timestamp.clone(),
+            input: call.tool(),
+            output: call.output(),
+            is_error: call.is_error(),
+        })
+        .collect()
+}
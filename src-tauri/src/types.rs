@@ -16,7 +16,84 @@ pub struct Session {
     pub display: String,
     pub timestamp: f64,
     pub project: String,
+    pub project_id: String,
     pub project_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SessionStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SessionSummary>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain: Option<SessionChain>,
+}
+
+/// Sessions linked by resuming or continuing a conversation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionChain {
+    pub previous: Option<String>,
+    /// The earliest continuation, when the session was resumed more than once.
+    pub next: Option<String>,
+    /// Every session in the chain, oldest first.
+    pub lineage: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SummarySource {
+    /// A `summary` record Claude Code wrote into the session file.
+    Recorded,
+    /// Built locally from the session's prompts, replies and edits.
+    Extractive,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub text: String,
+    pub source: SummarySource,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTotals {
+    pub input: u64,
+    pub output: u64,
+    pub cache_creation: u64,
+    pub cache_read: u64,
+}
+
+impl TokenTotals {
+    pub fn add(&mut self, usage: &TokenUsage) {
+        self.input += usage.input_tokens.unwrap_or(0);
+        self.output += usage.output_tokens.unwrap_or(0);
+        self.cache_creation += usage.cache_creation_input_tokens.unwrap_or(0);
+        self.cache_read += usage.cache_read_input_tokens.unwrap_or(0);
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_creation + self.cache_read
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStats {
+    pub message_count: usize,
+    /// Prompts the user typed, excluding tool results and injected content.
+    pub user_turns: usize,
+    pub assistant_turns: usize,
+    pub first_message_at: Option<String>,
+    pub last_message_at: Option<String>,
+    pub duration_ms: u64,
+    pub models: Vec<String>,
+    /// Times the model changed mid-session, including fallbacks.
+    pub model_switches: usize,
+    pub tokens: TokenTotals,
+    pub total_tokens: u64,
+    pub git_branch: Option<String>,
+    pub cwd: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,8 +104,26 @@ pub struct TokenUsage {
     pub cache_read_input_tokens: Option<u64>,
 }
 
+impl TokenUsage {
+    /// Tokens of context the request was sent with, whether cached or not.
+    pub fn context_size(&self) -> u64 {
+        self.input_tokens.unwrap_or(0)
+            + self.cache_creation_input_tokens.unwrap_or(0)
+            + self.cache_read_input_tokens.unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens.unwrap_or(0)
+            + self.output_tokens.unwrap_or(0)
+            + self.cache_creation_input_tokens.unwrap_or(0)
+            + self.cache_read_input_tokens.unwrap_or(0)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageContent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub role: String,
     pub content: ContentValue,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -66,6 +161,8 @@ pub enum ContentBlock {
         tool_use_id: Option<String>,
         content: Option<serde_json::Value>,
         is_error: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        truncated: Option<bool>,
     },
 }
 
@@ -84,9 +181,102 @@ pub struct ConversationMessage {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(rename = "gitBranch")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<MessageContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    /// Message a `summary` record describes the conversation up to.
+    #[serde(rename = "leafUuid")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leaf_uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<String>,
+    /// Text of `system` records.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(rename = "isCompactSummary")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_compact_summary: Option<bool>,
+    #[serde(rename = "compactMetadata")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_metadata: Option<CompactMetadata>,
+    #[serde(rename = "logicalParentUuid")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_parent_uuid: Option<String>,
+    /// Set on user messages Claude Code injects itself, such as the caveat
+    /// before local command output.
+    #[serde(rename = "isMeta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_meta: Option<bool>,
+    /// Set on messages of a subagent conversation.
+    #[serde(rename = "isSidechain")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_sidechain: Option<bool>,
+    #[serde(rename = "isApiErrorMessage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_api_error_message: Option<bool>,
+    /// Error details on `api_error` system records.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
+    #[serde(rename = "retryAttempt")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_attempt: Option<u32>,
+    #[serde(rename = "maxRetries")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Structured result of the tool call answered by this message. Only
+    /// read by the backend; the frontend renders the `tool_result` block.
+    #[serde(rename = "toolUseResult", default, skip_serializing)]
+    pub tool_use_result: Option<serde_json::Value>,
+    /// Tool call a `system` record (such as hook output) relates to.
+    #[serde(rename = "toolUseID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    /// Payload of `attachment` records, which newer Claude Code versions
+    /// use for hook results. Only read by the backend.
+    #[serde(default, skip_serializing)]
+    pub attachment: Option<serde_json::Value>,
+    /// What a `user` record actually holds; filled in by `from_line`.
+    #[serde(rename = "userKind", default, skip_deserializing)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_kind: Option<UserMessageKind>,
+}
+
+impl ConversationMessage {
+    /// Parses a transcript line and classifies user-role records.
+    pub fn from_line(line: &str) -> serde_json::Result<Self> {
+        let mut msg: ConversationMessage = serde_json::from_str(line)?;
+        msg.user_kind = crate::stats::classify_user_message(&msg);
+        Ok(msg)
+    }
+}
+
+/// The `user` role carries much more than what the user typed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UserMessageKind {
+    HumanPrompt,
+    ToolResult,
+    /// A slash command invocation, e.g. `/review`.
+    SlashCommand,
+    /// Output of local slash commands, and `!` shell commands with their output.
+    LocalCommandOutput,
+    /// Feedback from a hook that blocked or amended the turn.
+    HookFeedback,
+    /// Content Claude Code injected itself: caveats, system reminders,
+    /// interruption markers, compaction summaries and subagent prompts.
+    Meta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactMetadata {
+    pub trigger: Option<String>,
+    pub pre_tokens: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,3 +285,11 @@ pub struct StreamResult {
     pub messages: Vec<ConversationMessage>,
     pub next_offset: u64,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationWindow {
+    pub messages: Vec<ConversationMessage>,
+    pub start: usize,
+    pub total: usize,
+}
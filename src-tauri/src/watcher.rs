@@ -1,84 +1,272 @@
+use std::any::Any;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
-use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
-use tauri::{AppHandle, Emitter};
+use notify::{PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_mini::{
+    new_debouncer_opt, Config, DebouncedEvent, DebouncedEventKind, Debouncer,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
 
 use crate::storage::Storage;
 
-pub fn start_watcher(storage: Arc<Storage>, app: AppHandle) -> Result<()> {
-    let history_path = storage.claude_dir().join("history.jsonl");
-    let projects_dir = storage.projects_dir().to_path_buf();
-
-    let storage_clone = storage.clone();
-    let app_clone = app.clone();
-
-    let mut debouncer = new_debouncer(
-        std::time::Duration::from_millis(20),
-        move |events: Result<Vec<notify_debouncer_mini::DebouncedEvent>, notify::Error>| {
-            let events = match events {
-                Ok(e) => e,
-                Err(e) => {
-                    log::error!("Watcher error: {}", e);
-                    return;
-                }
-            };
-
-            let storage = storage_clone.clone();
-            let app = app_clone.clone();
-            let history_path = history_path.clone();
-
-            // Process events on the async runtime
-            tauri::async_runtime::spawn(async move {
-                let mut sessions_changed = false;
-                let mut changed_sessions: Vec<(String, PathBuf)> = Vec::new();
-
-                for event in events {
-                    if event.kind != DebouncedEventKind::Any {
-                        continue;
-                    }
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(20);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatcherMode {
+    Native,
+    Polling,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub mode: Option<WatcherMode>,
+    pub last_event_at: Option<u64>,
+    pub error_count: u64,
+    pub last_error: Option<String>,
+}
+
+/// Keeps a debouncer alive; dropping it stops the underlying watcher.
+struct ActiveWatcher {
+    mode: WatcherMode,
+    _debouncer: Box<dyn Any + Send>,
+}
+
+impl ActiveWatcher {
+    fn new<W: Watcher + Send + 'static>(mode: WatcherMode, debouncer: Debouncer<W>) -> Self {
+        ActiveWatcher {
+            mode,
+            _debouncer: Box::new(debouncer),
+        }
+    }
+}
+
+#[derive(Default)]
+struct WatcherHealth {
+    last_event_at: AtomicU64,
+    error_count: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl WatcherHealth {
+    fn record_event(&self) {
+        self.last_event_at.store(now_millis(), Ordering::Relaxed);
+    }
+
+    fn record_error(&self, message: String) {
+        self.error_count.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().unwrap() = Some(message);
+    }
+}
+
+/// Owns the file watcher so it can be stopped, restarted and inspected
+/// instead of living until the process exits.
+pub struct WatcherHandle {
+    storage: Arc<Storage>,
+    app: AppHandle,
+    active: Mutex<Option<ActiveWatcher>>,
+    health: Arc<WatcherHealth>,
+}
+
+impl WatcherHandle {
+    pub fn new(storage: Arc<Storage>, app: AppHandle) -> Self {
+        WatcherHandle {
+            storage,
+            app,
+            active: Mutex::new(None),
+            health: Arc::new(WatcherHealth::default()),
+        }
+    }
+
+    /// Starts the native watcher, falling back to polling when the OS watch
+    /// limit (e.g. inotify `max_user_watches`) is exhausted.
+    pub fn start(&self) -> Result<()> {
+        let mut active = self.active.lock().unwrap();
+        if active.is_some() {
+            return Ok(());
+        }
+
+        match self.create::<RecommendedWatcher>(Config::default()) {
+            Ok(debouncer) => {
+                *active = Some(ActiveWatcher::new(WatcherMode::Native, debouncer));
+                Ok(())
+            }
+            Err(e) if is_watch_limit(&e) => {
+                log::warn!(
+                    "Native file watcher unavailable ({}), falling back to polling",
+                    e
+                );
+                self.health.record_error(e.to_string());
+                *active = Some(ActiveWatcher::new(
+                    WatcherMode::Polling,
+                    self.create_polling()?,
+                ));
+                Ok(())
+            }
+            Err(e) => {
+                self.health.record_error(e.to_string());
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Replaces whatever watcher is running with a polling one.
+    pub fn start_polling(&self) -> Result<()> {
+        let mut active = self.active.lock().unwrap();
+        if active.as_ref().map(|a| a.mode) == Some(WatcherMode::Polling) {
+            return Ok(());
+        }
+        *active = None;
+        *active = Some(ActiveWatcher::new(
+            WatcherMode::Polling,
+            self.create_polling()?,
+        ));
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        // Dropping the debouncer shuts down its event loop
+        self.active.lock().unwrap().take();
+    }
+
+    pub fn restart(&self) -> Result<()> {
+        self.stop();
+        self.start()
+    }
 
-                    let path = &event.path;
-                    let path_str = path.to_string_lossy();
+    pub fn status(&self) -> WatcherStatus {
+        let mode = self.active.lock().unwrap().as_ref().map(|a| a.mode);
+        let last_event_at = match self.health.last_event_at.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
+        };
 
-                    if *path == history_path {
-                        sessions_changed = true;
-                    } else if path_str.ends_with(".jsonl") {
-                        if let Some(file_name) = path.file_stem() {
-                            let session_id = file_name.to_string_lossy().to_string();
-                            changed_sessions.push((session_id, path.clone()));
+        WatcherStatus {
+            running: mode.is_some(),
+            mode,
+            last_event_at,
+            error_count: self.health.error_count.load(Ordering::Relaxed),
+            last_error: self.health.last_error.lock().unwrap().clone(),
+        }
+    }
+
+    fn create_polling(&self) -> notify::Result<Debouncer<PollWatcher>> {
+        let notify_config = notify::Config::default().with_poll_interval(POLL_INTERVAL);
+        self.create::<PollWatcher>(Config::default().with_notify_config(notify_config))
+    }
+
+    fn create<W: Watcher>(&self, config: Config) -> notify::Result<Debouncer<W>> {
+        let history_path = self.storage.claude_dir().join("history.jsonl");
+        let projects_dir = self.storage.projects_dir().to_path_buf();
+
+        let storage = self.storage.clone();
+        let app = self.app.clone();
+        let health = self.health.clone();
+        let handler_history_path = history_path.clone();
+
+        let mut debouncer = new_debouncer_opt::<_, W>(
+            config.with_timeout(DEBOUNCE_TIMEOUT),
+            move |events: Result<Vec<DebouncedEvent>, notify::Error>| {
+                let events = match events {
+                    Ok(e) => e,
+                    Err(e) => {
+                        log::error!("Watcher error: {}", e);
+                        health.record_error(e.to_string());
+                        if is_watch_limit(&e) {
+                            request_polling_fallback(app.clone());
                         }
+                        return;
                     }
-                }
-
-                if sessions_changed {
-                    storage.invalidate_history_cache().await;
-                    let _ = app.emit("sessions-update", ());
-                }
-
-                for (session_id, path) in changed_sessions {
-                    storage
-                        .add_to_file_index(session_id.clone(), path)
-                        .await;
-                    let _ = app.emit("conversation-update", &session_id);
-                }
-            });
-        },
-    )?;
-
-    debouncer
-        .watcher()
-        .watch(&storage.claude_dir().join("history.jsonl"), notify::RecursiveMode::NonRecursive)
-        .ok(); // history.jsonl may not exist yet
-
-    debouncer
-        .watcher()
-        .watch(&projects_dir, notify::RecursiveMode::Recursive)?;
-
-    // Leak the debouncer so it lives for the lifetime of the app
-    // This is intentional - the watcher should run until the app exits
-    std::mem::forget(debouncer);
-
-    Ok(())
+                };
+
+                health.record_event();
+                tauri::async_runtime::spawn(handle_events(
+                    storage.clone(),
+                    app.clone(),
+                    handler_history_path.clone(),
+                    events,
+                ));
+            },
+        )?;
+
+        debouncer
+            .watcher()
+            .watch(&history_path, RecursiveMode::NonRecursive)
+            .ok(); // history.jsonl may not exist yet
+
+        debouncer
+            .watcher()
+            .watch(&projects_dir, RecursiveMode::Recursive)?;
+
+        Ok(debouncer)
+    }
+}
+
+fn is_watch_limit(error: &notify::Error) -> bool {
+    matches!(error.kind, notify::ErrorKind::MaxFilesWatch)
+}
+
+fn request_polling_fallback(app: AppHandle) {
+    // Runs off the debouncer thread, since swapping watchers drops the current one
+    tauri::async_runtime::spawn(async move {
+        if let Some(watcher) = app.try_state::<Arc<WatcherHandle>>() {
+            log::warn!("File watch limit reached, switching to polling");
+            if let Err(e) = watcher.start_polling() {
+                log::error!("Failed to start polling watcher: {}", e);
+            }
+        }
+    });
+}
+
+async fn handle_events(
+    storage: Arc<Storage>,
+    app: AppHandle,
+    history_path: PathBuf,
+    events: Vec<DebouncedEvent>,
+) {
+    let mut sessions_changed = false;
+    let mut changed_sessions: Vec<(String, PathBuf)> = Vec::new();
+
+    for event in events {
+        if event.kind != DebouncedEventKind::Any {
+            continue;
+        }
+
+        let path = &event.path;
+        let path_str = path.to_string_lossy();
+
+        if *path == history_path {
+            sessions_changed = true;
+        } else if path_str.ends_with(".jsonl") {
+            if let Some(file_name) = path.file_stem() {
+                let session_id = file_name.to_string_lossy().to_string();
+                changed_sessions.push((session_id, path.clone()));
+            }
+        }
+    }
+
+    if sessions_changed {
+        storage.invalidate_history_cache().await;
+        let _ = app.emit("sessions-update", ());
+    }
+
+    for (session_id, path) in changed_sessions {
+        storage.add_to_file_index(session_id.clone(), path).await;
+        let _ = app.emit("conversation-update", &session_id);
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
 }
@@ -10,22 +10,97 @@ export interface Session {
   display: string;
   timestamp: number;
   project: string;
+  projectId: string;
   projectName: string;
+  stats?: SessionStats;
+  summary?: SessionSummary;
+  tags?: string[];
+  chain?: SessionChain;
+}
+
+export interface SessionChain {
+  previous: string | null;
+  next: string | null;
+  lineage: string[];
+}
+
+export interface SessionSummary {
+  text: string;
+  source: "recorded" | "extractive";
+}
+
+export interface Project {
+  id: string;
+  path: string;
+  name: string;
+  sessionCount: number;
+  lastActivity: number;
+}
+
+export interface TokenTotals {
+  input: number;
+  output: number;
+  cacheCreation: number;
+  cacheRead: number;
+}
+
+export interface SessionStats {
+  messageCount: number;
+  userTurns: number;
+  assistantTurns: number;
+  firstMessageAt: string | null;
+  lastMessageAt: string | null;
+  durationMs: number;
+  models: string[];
+  modelSwitches: number;
+  tokens: TokenTotals;
+  totalTokens: number;
+  gitBranch: string | null;
+  cwd: string | null;
 }
 
 export interface ConversationMessage {
-  type: "user" | "assistant" | "summary" | "file-history-snapshot";
+  type: "user" | "assistant" | "summary" | "system" | "file-history-snapshot";
   uuid?: string;
   parentUuid?: string;
   timestamp?: string;
   sessionId?: string;
+  cwd?: string;
+  gitBranch?: string;
   message?: {
+    id?: string;
     role: string;
     content: string | ContentBlock[];
     model?: string;
     usage?: TokenUsage;
   };
   summary?: string;
+  leafUuid?: string;
+  subtype?: string;
+  content?: string;
+  isCompactSummary?: boolean;
+  compactMetadata?: CompactMetadata;
+  logicalParentUuid?: string;
+  isMeta?: boolean;
+  isSidechain?: boolean;
+  isApiErrorMessage?: boolean;
+  error?: unknown;
+  retryAttempt?: number;
+  maxRetries?: number;
+  userKind?: UserMessageKind;
+}
+
+export type UserMessageKind =
+  | "humanPrompt"
+  | "toolResult"
+  | "slashCommand"
+  | "localCommandOutput"
+  | "hookFeedback"
+  | "meta";
+
+export interface CompactMetadata {
+  trigger: string | null;
+  preTokens: number | null;
 }
 
 export interface ContentBlock {
@@ -38,6 +113,7 @@ export interface ContentBlock {
   tool_use_id?: string;
   content?: string | ContentBlock[];
   is_error?: boolean;
+  truncated?: boolean;
 }
 
 export interface TokenUsage {
@@ -51,3 +127,650 @@ export interface StreamResult {
   messages: ConversationMessage[];
   nextOffset: number;
 }
+
+export interface ConversationWindow {
+  messages: ConversationMessage[];
+  start: number;
+  total: number;
+}
+
+export interface SessionFilter {
+  project?: string;
+  from?: number;
+  to?: number;
+  text?: string;
+  model?: string;
+  modelSwitched?: boolean;
+  tag?: string;
+  minTokens?: number;
+}
+
+export type SessionSort = "newest" | "oldest" | "mostTokens" | "longest" | "mostMessages";
+
+export interface SessionQuery {
+  filter?: SessionFilter;
+  sort?: SessionSort;
+  cursor?: string;
+  limit?: number;
+}
+
+export interface SessionPage {
+  sessions: Session[];
+  nextCursor: string | null;
+  total: number;
+}
+
+export interface BranchSpan {
+  branch: string;
+  firstSeen: string | null;
+  lastSeen: string | null;
+  messageCount: number;
+}
+
+export interface GitCommit {
+  id: string;
+  summary: string;
+  author: string;
+  timestamp: number;
+  branches: string[];
+}
+
+export interface GitContext {
+  cwd: string | null;
+  repoRoot: string | null;
+  branches: BranchSpan[];
+  commits: GitCommit[];
+}
+
+export type Shell = "bash" | "zsh" | "fish" | "powershell";
+
+export interface SessionSide {
+  sessionId: string;
+  turns: number;
+  tokens: TokenTotals;
+  totalTokens: number;
+  durationMs: number;
+  models: string[];
+  toolCalls: number;
+  toolErrors: number;
+  toolCounts: Record<string, number>;
+  filesTouched: string[];
+  outcome: string | null;
+}
+
+export interface ToolCallSummary {
+  name: string;
+  target: string | null;
+  isError: boolean;
+}
+
+export interface TurnComparison {
+  index: number;
+  leftPrompt: string | null;
+  rightPrompt: string | null;
+  leftTools: ToolCallSummary[];
+  rightTools: ToolCallSummary[];
+  leftTokens: number;
+  rightTokens: number;
+}
+
+export interface ToolStep {
+  kind: "same" | "changed" | "leftOnly" | "rightOnly";
+  left: ToolCallSummary | null;
+  right: ToolCallSummary | null;
+}
+
+export interface SessionComparison {
+  left: SessionSide;
+  right: SessionSide;
+  turns: TurnComparison[];
+  toolSteps: ToolStep[];
+  filesOnlyLeft: string[];
+  filesOnlyRight: string[];
+  filesInBoth: string[];
+}
+
+export type Granularity = "hour" | "day";
+
+export interface ActivityQuery {
+  granularity?: Granularity;
+  project?: string;
+  from?: number;
+  to?: number;
+  utcOffsetMinutes?: number;
+}
+
+export interface ActivityBucket {
+  start: number;
+  projectId: string;
+  project: string;
+  messages: number;
+  prompts: number;
+  tokens: number;
+}
+
+export interface DayActivity {
+  date: string;
+  messages: number;
+  prompts: number;
+  tokens: number;
+  sessions: number;
+}
+
+export interface ActivityReport {
+  granularity: Granularity;
+  buckets: ActivityBucket[];
+  days: DayActivity[];
+}
+
+export interface CompactionBoundary {
+  uuid: string | null;
+  timestamp: string | null;
+  trigger: string | null;
+  logicalParentUuid: string | null;
+  summary: string | null;
+}
+
+export interface CompactionSegment {
+  index: number;
+  boundary: CompactionBoundary | null;
+  messageCount: number;
+  firstMessageAt: string | null;
+  lastMessageAt: string | null;
+  contextTokensStart: number | null;
+  contextTokensEnd: number | null;
+  tokens: TokenTotals;
+}
+
+export type TodoStatus = "pending" | "in_progress" | "completed";
+
+export interface TodoItem {
+  content: string;
+  status: TodoStatus;
+  activeForm: string | null;
+  id: string | null;
+}
+
+export interface EditOperation {
+  old_string: string;
+  new_string: string;
+  replace_all: boolean | null;
+}
+
+export type ToolInput =
+  | {
+      tool: "Bash";
+      command: string;
+      description: string | null;
+      timeout: number | null;
+      run_in_background: boolean | null;
+    }
+  | { tool: "Read"; file_path: string; offset: number | null; limit: number | null }
+  | { tool: "Write"; file_path: string; content: string }
+  | { tool: "Edit"; file_path: string; old_string: string; new_string: string; replace_all: boolean | null }
+  | { tool: "MultiEdit"; file_path: string; edits: EditOperation[] }
+  | { tool: "Glob"; pattern: string; path: string | null }
+  | {
+      tool: "Grep";
+      pattern: string;
+      path: string | null;
+      glob: string | null;
+      type: string | null;
+      output_mode: string | null;
+    }
+  | {
+      tool: "Task";
+      description: string;
+      prompt: string;
+      subagent_type: string | null;
+      model: string | null;
+      run_in_background: boolean | null;
+      resume: string | null;
+    }
+  | { tool: "TodoWrite"; todos: TodoItem[] }
+  | { tool: "WebFetch"; url: string; prompt: string | null }
+  | { tool: "WebSearch"; query: string; allowed_domains: string[] | null; blocked_domains: string[] | null }
+  | {
+      tool: "NotebookEdit";
+      notebook_path: string;
+      new_source: string;
+      cell_id: string | null;
+      cell_type: string | null;
+      edit_mode: string | null;
+    }
+  | { tool: "Unknown"; name: string; input: unknown };
+
+export type ToolOutput =
+  | {
+      tool: "Bash";
+      output: { stdout: string; stderr: string; interrupted: boolean; returnCodeInterpretation: string | null };
+    }
+  | {
+      tool: "Read";
+      output: {
+        file: { filePath: string; numLines: number | null; startLine: number | null; totalLines: number | null };
+      };
+    }
+  | { tool: "Write"; output: { type: string | null; filePath: string } }
+  | {
+      tool: "Edit" | "MultiEdit";
+      output: { filePath: string; userModified: boolean | null; replaceAll: boolean | null };
+    }
+  | {
+      tool: "Glob";
+      output: { filenames: string[]; numFiles: number | null; truncated: boolean | null; durationMs: number | null };
+    }
+  | {
+      tool: "Grep";
+      output: { mode: string | null; filenames: string[]; numFiles: number | null; numLines: number | null };
+    }
+  | {
+      tool: "Task";
+      output: { totalDurationMs: number | null; totalTokens: number | null; totalToolUseCount: number | null };
+    }
+  | { tool: "TodoWrite"; output: { oldTodos: TodoItem[]; newTodos: TodoItem[] } }
+  | {
+      tool: "WebFetch";
+      output: {
+        url: string | null;
+        code: number | null;
+        codeText: string | null;
+        bytes: number | null;
+        durationMs: number | null;
+      };
+    }
+  | { tool: "WebSearch"; output: { query: string | null; durationSeconds: number | null } }
+  | {
+      tool: "NotebookEdit";
+      output: { cell_id: string | null; cell_type: string | null; edit_mode: string | null; error: string | null };
+    }
+  | { tool: "Text"; output: string }
+  | { tool: "Unknown"; output: unknown };
+
+export interface ToolCallRecord {
+  toolUseId: string | null;
+  uuid: string | null;
+  timestamp: string | null;
+  input: ToolInput;
+  output: ToolOutput | null;
+  isError: boolean;
+}
+
+export interface BashHistoryQuery {
+  project?: string;
+  sessionId?: string;
+  text?: string;
+  errorsOnly?: boolean;
+  from?: number;
+  to?: number;
+  limit?: number;
+}
+
+export interface BashCommand {
+  sessionId: string;
+  projectId: string;
+  project: string;
+  uuid: string | null;
+  timestamp: string | null;
+  command: string;
+  description: string | null;
+  cwd: string | null;
+  runInBackground: boolean;
+  isError: boolean;
+  exitCode: number | null;
+  interrupted: boolean;
+}
+
+export type Severity = "low" | "medium" | "high" | "critical";
+
+export type AuditRuleKind =
+  | { kind: "shellCommand"; patterns: string[] }
+  | { kind: "writeOutsideProject"; allowedPaths?: string[] }
+  | { kind: "sensitivePath"; patterns: string[] }
+  | { kind: "networkFetch"; allowedHosts: string[] };
+
+export type AuditRule = {
+  id: string;
+  description: string;
+  severity: Severity;
+  enabled?: boolean;
+} & AuditRuleKind;
+
+export interface AuditConfig {
+  rules: AuditRule[];
+}
+
+export interface AuditFinding {
+  ruleId: string;
+  severity: Severity;
+  message: string;
+  sessionId: string;
+  project: string | null;
+  uuid: string | null;
+  timestamp: string | null;
+  tool: string;
+  toolUseId: string | null;
+  evidence: string;
+}
+
+export interface AuditReport {
+  sessionsScanned: number;
+  findings: AuditFinding[];
+  bySeverity: Partial<Record<Severity, number>>;
+  byRule: Record<string, number>;
+}
+
+export type TodoChangeKind = "added" | "statusChanged" | "removed";
+
+export interface TodoChange {
+  kind: TodoChangeKind;
+  content: string;
+  from: TodoStatus | null;
+  to: TodoStatus | null;
+  uuid: string | null;
+  timestamp: string | null;
+}
+
+export interface TrackedTodo {
+  content: string;
+  activeForm: string | null;
+  status: TodoStatus;
+  addedAt: string | null;
+  startedAt: string | null;
+  completedAt: string | null;
+  removedAt: string | null;
+}
+
+export interface TodoProgress {
+  updates: number;
+  items: TrackedTodo[];
+  timeline: TodoChange[];
+  pending: number;
+  inProgress: number;
+  completed: number;
+}
+
+export type FailureKind = "toolError" | "apiError" | "interrupted";
+
+export interface FailedToolUse {
+  toolUseId: string | null;
+  name: string;
+  target: string | null;
+  uuid: string | null;
+}
+
+export interface FailureEvent {
+  kind: FailureKind;
+  uuid: string | null;
+  timestamp: string | null;
+  message: string;
+  toolUse: FailedToolUse | null;
+  retryAttempt: number | null;
+  maxRetries: number | null;
+}
+
+export interface RetrySequence {
+  kind: FailureKind;
+  tool: string | null;
+  target: string | null;
+  failedAttempts: number;
+  recovered: boolean;
+  startedAt: string | null;
+  endedAt: string | null;
+}
+
+export interface FailureTimeline {
+  events: FailureEvent[];
+  retrySequences: RetrySequence[];
+  toolErrors: number;
+  apiErrors: number;
+  interruptions: number;
+}
+
+export interface FollowingAction {
+  kind: "text" | "toolUse";
+  tool: string | null;
+  target: string | null;
+  preview: string | null;
+}
+
+export interface ThinkingBlock {
+  uuid: string | null;
+  timestamp: string | null;
+  model: string | null;
+  text: string;
+  chars: number;
+  words: number;
+  followingAction: FollowingAction | null;
+}
+
+export interface ThinkingVolume {
+  blocks: number;
+  chars: number;
+  words: number;
+}
+
+export interface SessionThinking {
+  blocks: ThinkingBlock[];
+  total: ThinkingVolume;
+  byModel: Record<string, ThinkingVolume>;
+}
+
+export interface SessionThinkingVolume extends ThinkingVolume {
+  sessionId: string;
+  project: string;
+}
+
+export interface ThinkingReport {
+  total: ThinkingVolume;
+  byModel: Record<string, ThinkingVolume>;
+  sessions: SessionThinkingVolume[];
+}
+
+export interface ModelUsage {
+  model: string;
+  responses: number;
+  tokens: TokenTotals;
+  totalTokens: number;
+  firstUsedAt: string | null;
+  lastUsedAt: string | null;
+}
+
+export type SwitchKind = "switch" | "fallback";
+
+export interface ModelSwitch {
+  kind: SwitchKind;
+  from: string;
+  to: string;
+  uuid: string | null;
+  timestamp: string | null;
+}
+
+export interface ModelBreakdown {
+  models: ModelUsage[];
+  switches: ModelSwitch[];
+}
+
+export interface PromptQuery {
+  project?: string;
+  minUses?: number;
+  limit?: number;
+}
+
+export interface PromptUsage {
+  key: string;
+  text: string;
+  uses: number;
+  sessionIds: string[];
+  projects: string[];
+  firstUsedAt: number | null;
+  lastUsedAt: number | null;
+  saved: boolean;
+  tags: string[];
+}
+
+export interface SavedPrompt {
+  key: string;
+  text: string;
+  title: string | null;
+  tags: string[];
+  savedAt: number;
+}
+
+export interface SlashCommandEvent {
+  name: string;
+  args: string | null;
+  output: string | null;
+  isError: boolean;
+  uuid: string | null;
+  timestamp: string | null;
+}
+
+export type HookStatus = "success" | "nonBlockingError" | "blockingError" | "cancelled";
+
+export interface HookEvent {
+  event: string;
+  name: string;
+  command: string | null;
+  status: HookStatus;
+  exitCode: number | null;
+  output: string | null;
+  toolUseId: string | null;
+  uuid: string | null;
+  timestamp: string | null;
+}
+
+export interface SessionInvocations {
+  commands: SlashCommandEvent[];
+  hooks: HookEvent[];
+}
+
+export interface SlashCommandStats {
+  name: string;
+  uses: number;
+  errors: number;
+  sessions: number;
+  lastUsedAt: string | null;
+}
+
+export interface HookStats {
+  name: string;
+  event: string;
+  command: string | null;
+  runs: number;
+  failures: number;
+  blocked: number;
+  sessions: number;
+  lastRunAt: string | null;
+}
+
+export interface InvocationReport {
+  commands: SlashCommandStats[];
+  hooks: HookStats[];
+}
+
+export interface McpToolStats {
+  tool: string;
+  calls: number;
+  errors: number;
+  resultChars: number;
+}
+
+export interface McpServerStats {
+  server: string;
+  calls: number;
+  errors: number;
+  resultChars: number;
+  sessions: number;
+  lastUsedAt: string | null;
+  tools: McpToolStats[];
+}
+
+export interface McpReport {
+  totalCalls: number;
+  totalErrors: number;
+  servers: McpServerStats[];
+}
+
+export interface ContextPoint {
+  turn: number;
+  uuid: string | null;
+  timestamp: string | null;
+  model: string | null;
+  contextTokens: number;
+  outputTokens: number;
+  delta: number;
+  afterCompaction: boolean;
+}
+
+export interface ContextContributor {
+  kind: UserMessageKind;
+  uuid: string | null;
+  tool: string | null;
+  target: string | null;
+  toolUseId: string | null;
+  chars: number;
+}
+
+export interface ContextJump {
+  turn: number;
+  uuid: string | null;
+  timestamp: string | null;
+  delta: number;
+  contextTokens: number;
+  causes: ContextContributor[];
+}
+
+export interface ContextGrowth {
+  points: ContextPoint[];
+  largestJumps: ContextJump[];
+  peakTokens: number;
+  compactions: number;
+}
+
+export interface CacheEfficiency {
+  responses: number;
+  inputTokens: number;
+  cacheCreationTokens: number;
+  cacheReadTokens: number;
+  hitRatio: number;
+  readToCreatedRatio: number | null;
+  tokensSaved: number;
+  estimatedSavingsUsd: number | null;
+  unpricedModels: string[];
+}
+
+export type CacheBustCause = "idleGap" | "modelSwitch" | "prefixChanged";
+
+export interface CacheBust {
+  cause: CacheBustCause;
+  uuid: string | null;
+  timestamp: string | null;
+  idleMs: number | null;
+  cacheCreationTokens: number;
+  cacheReadTokens: number;
+  estimatedCostUsd: number | null;
+}
+
+export interface SessionCacheEfficiency extends CacheEfficiency {
+  busts: CacheBust[];
+}
+
+export interface SessionCacheSummary extends CacheEfficiency {
+  sessionId: string;
+  project: string;
+  busts: number;
+}
+
+export interface ProjectCacheSummary extends CacheEfficiency {
+  project: string;
+  projectId: string;
+  busts: number;
+}
+
+export interface CacheReport {
+  total: CacheEfficiency;
+  busts: number;
+  projects: ProjectCacheSummary[];
+  sessions: SessionCacheSummary[];
+}